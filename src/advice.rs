@@ -0,0 +1,141 @@
+//! Page-cache hints for ranged file reads
+
+use std::fs::File;
+use std::io::{self, Read, Take};
+use std::ops::Range;
+
+/// The access pattern of a ranged file read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// A large range that is read sequentially and benefits from readahead
+    Sequential,
+    /// A small range that benefits from reduced readahead
+    Random,
+}
+
+/// A policy to classify ranged file reads and to apply the appropriate page-cache hints
+///
+/// # Note
+/// The hints are applied via `posix_fadvise(2)` if the `fadvise` feature is enabled on Linux; otherwise, they are no-ops
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdvicePolicy {
+    /// Ranges of at least this size are considered sequential, smaller ranges are considered random
+    pub sequential_threshold: u64,
+    /// Ranges of at least this size are dropped from the page cache after they have been streamed (`None` to disable)
+    pub dontneed_threshold: Option<u64>,
+}
+impl AdvicePolicy {
+    /// Classifies a range with the given length
+    pub const fn classify(&self, len: u64) -> Access {
+        match len >= self.sequential_threshold {
+            true => Access::Sequential,
+            false => Access::Random,
+        }
+    }
+
+    /// Applies the access hints for the given range of the file
+    pub fn apply(&self, file: &File, range: &Range<u64>) -> io::Result<()> {
+        let len = range.end.saturating_sub(range.start);
+        match self.classify(len) {
+            Access::Sequential => {
+                fadvise(file, range, Advice::Sequential)?;
+                fadvise(file, range, Advice::WillNeed)
+            }
+            Access::Random => fadvise(file, range, Advice::Random),
+        }
+    }
+
+    /// Whether the given range should be dropped from the page cache after it has been streamed
+    pub fn should_drop(&self, range: &Range<u64>) -> bool {
+        let len = range.end.saturating_sub(range.start);
+        self.dontneed_threshold.is_some_and(|threshold| len >= threshold)
+    }
+}
+impl Default for AdvicePolicy {
+    fn default() -> Self {
+        Self { sequential_threshold: 1024 * 1024, dontneed_threshold: None }
+    }
+}
+
+/// A reader that is backed by a file
+pub(crate) trait FileReader
+where
+    Self: Read,
+{
+    /// The underlying file
+    fn file(&self) -> &File;
+}
+impl FileReader for Take<File> {
+    fn file(&self) -> &File {
+        self.get_ref()
+    }
+}
+
+/// A reader over a file range that drops the range from the page cache once it is dropped
+#[derive(Debug)]
+pub(crate) struct DontNeedReader<T>
+where
+    T: FileReader,
+{
+    /// The underlying reader
+    pub reader: T,
+    /// The range to drop
+    pub range: Range<u64>,
+}
+impl<T> Read for DontNeedReader<T>
+where
+    T: FileReader,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+impl<T> Drop for DontNeedReader<T>
+where
+    T: FileReader,
+{
+    fn drop(&mut self) {
+        // Note: The hint is best-effort, so errors are ignored
+        let _ = fadvise(self.reader.file(), &self.range, Advice::DontNeed);
+    }
+}
+
+/// A page-cache hint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Advice {
+    /// `POSIX_FADV_SEQUENTIAL`
+    Sequential,
+    /// `POSIX_FADV_RANDOM`
+    Random,
+    /// `POSIX_FADV_WILLNEED`
+    WillNeed,
+    /// `POSIX_FADV_DONTNEED`
+    DontNeed,
+}
+
+/// Applies the given hint to the given range of the file
+#[cfg(all(target_os = "linux", feature = "fadvise"))]
+fn fadvise(file: &File, range: &Range<u64>, advice: Advice) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    // Convert the arguments
+    let advice = match advice {
+        Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+        Advice::Random => libc::POSIX_FADV_RANDOM,
+        Advice::WillNeed => libc::POSIX_FADV_WILLNEED,
+        Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+    };
+    let offset = libc::off_t::try_from(range.start).map_err(io::Error::other)?;
+    let len = libc::off_t::try_from(range.end.saturating_sub(range.start)).map_err(io::Error::other)?;
+
+    // SAFETY: `posix_fadvise` has no memory safety preconditions
+    match unsafe { libc::posix_fadvise(file.as_raw_fd(), offset, len, advice) } {
+        0 => Ok(()),
+        errno => Err(io::Error::from_raw_os_error(errno)),
+    }
+}
+/// Applies the given hint to the given range of the file (no-op)
+#[cfg(not(all(target_os = "linux", feature = "fadvise")))]
+fn fadvise(_file: &File, _range: &Range<u64>, _advice: Advice) -> io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,129 @@
+//! Pluggable storage backends to look up range-servable objects by key
+
+use crate::filecache::{FileCache, OpenFile};
+use crate::representation::Representation;
+use ehttpd::bytes::Data;
+use ehttpd::error::Error;
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// A storage backend that looks up servable objects by key
+pub trait Backend {
+    /// The object type
+    type Object: Representation;
+
+    /// Looks up the object with the given key if it exists
+    fn lookup(&self, key: &str) -> Result<Option<Self::Object>, Error>;
+}
+
+/// A filesystem backend that maps keys to files below a root directory
+#[derive(Debug, Clone)]
+pub struct FsBackend {
+    /// The root directory
+    root: PathBuf,
+    /// The file cache if any
+    cache: Option<Arc<FileCache>>,
+}
+impl FsBackend {
+    /// Creates a new filesystem backend rooted at the given directory
+    pub fn new<T>(root: T) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        Self { root: root.into(), cache: None }
+    }
+    /// Creates a new filesystem backend rooted at the given directory that caches open files in the given cache
+    pub fn with_cache<T>(root: T, cache: Arc<FileCache>) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        Self { root: root.into(), cache: Some(cache) }
+    }
+
+    /// Resolves the given key to a path below the root directory
+    ///
+    /// # Note
+    /// This function returns `None` if the key is not a plain relative path (e.g. if it contains `..`)
+    pub fn resolve(&self, key: &str) -> Option<PathBuf> {
+        let mut path = self.root.clone();
+        for component in Path::new(key).components() {
+            match component {
+                Component::Normal(component) => path.push(component),
+                Component::CurDir => continue,
+                _ => return None,
+            }
+        }
+        Some(path)
+    }
+}
+impl Backend for FsBackend {
+    type Object = OpenFile;
+
+    fn lookup(&self, key: &str) -> Result<Option<Self::Object>, Error> {
+        // Open the file or get it from the cache
+        let Some(path) = self.resolve(key) else {
+            return Ok(None);
+        };
+        let file = match &self.cache {
+            Some(cache) => cache.get(path),
+            None => OpenFile::open(path),
+        };
+
+        // Only serve regular files
+        match file {
+            Ok(file) if file.file_type().is_file() => Ok(Some(file)),
+            Ok(_) => Ok(None),
+            Err(e) if is_not_found(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// A concurrent in-memory backend
+#[derive(Debug, Default)]
+pub struct MemoryBackend {
+    /// The stored objects
+    objects: RwLock<HashMap<String, Data>>,
+}
+impl MemoryBackend {
+    /// Creates a new empty in-memory backend
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts or replaces the object with the given key and returns the previous object if any
+    pub fn insert<K, V>(&self, key: K, object: V) -> Option<Data>
+    where
+        K: Into<String>,
+        V: Into<Data>,
+    {
+        let mut objects = self.objects.write().expect("backend lock is poisoned");
+        objects.insert(key.into(), object.into())
+    }
+    /// Removes the object with the given key and returns it if any
+    pub fn remove(&self, key: &str) -> Option<Data> {
+        let mut objects = self.objects.write().expect("backend lock is poisoned");
+        objects.remove(key)
+    }
+}
+impl Backend for MemoryBackend {
+    type Object = Data;
+
+    fn lookup(&self, key: &str) -> Result<Option<Self::Object>, Error> {
+        let objects = self.objects.read().expect("backend lock is poisoned");
+        Ok(objects.get(key).cloned())
+    }
+}
+
+/// Checks whether the error is caused by a missing file
+fn is_not_found(error: &Error) -> bool {
+    let Some(source) = &error.source else {
+        return false;
+    };
+    match source.downcast_ref::<std::io::Error>() {
+        Some(source) => source.kind() == ErrorKind::NotFound,
+        None => false,
+    }
+}
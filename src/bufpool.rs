@@ -0,0 +1,138 @@
+//! A reusable buffer pool for ranged body streaming
+
+use std::fmt::{self, Debug, Formatter};
+use std::io::{self, BufRead, Read};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// The default buffer size, which matches the default `BufReader` capacity
+pub const DEFAULT_BUFFER_SIZE: usize = 8 * 1024;
+
+/// A pool of reusable I/O buffers with a fixed size
+#[derive(Debug)]
+pub struct BufferPool {
+    /// The idle buffers
+    idle: Mutex<Vec<Box<[u8]>>>,
+    /// The size of each buffer
+    buffer_size: usize,
+    /// The maximum amount of idle buffers to keep
+    max_idle: usize,
+}
+impl BufferPool {
+    /// Creates a new buffer pool with the given buffer size that keeps at most `max_idle` idle buffers
+    pub fn new(buffer_size: usize, max_idle: usize) -> Self {
+        Self { idle: Mutex::new(Vec::new()), buffer_size: buffer_size.max(1), max_idle }
+    }
+    /// The global buffer pool with [`DEFAULT_BUFFER_SIZE`]-sized buffers
+    pub fn global() -> &'static Arc<Self> {
+        static GLOBAL: OnceLock<Arc<BufferPool>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(Self::new(DEFAULT_BUFFER_SIZE, 1024)))
+    }
+
+    /// The size of each buffer
+    pub const fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+    /// The amount of idle buffers
+    pub fn idle(&self) -> usize {
+        let idle = self.idle.lock().expect("pool lock is poisoned");
+        idle.len()
+    }
+
+    /// Borrows a buffer from the pool or allocates a new buffer if the pool is empty
+    pub fn take(self: &Arc<Self>) -> PooledBuffer {
+        let mut idle = self.idle.lock().expect("pool lock is poisoned");
+        let buf = idle.pop().unwrap_or_else(|| vec![0; self.buffer_size].into_boxed_slice());
+        PooledBuffer { buf, pool: self.clone() }
+    }
+}
+
+/// A buffer borrowed from a [`BufferPool`] that is returned to the pool on drop
+pub struct PooledBuffer {
+    /// The buffer
+    buf: Box<[u8]>,
+    /// The originating pool
+    pool: Arc<BufferPool>,
+}
+impl AsRef<[u8]> for PooledBuffer {
+    fn as_ref(&self) -> &[u8] {
+        &self.buf
+    }
+}
+impl AsMut<[u8]> for PooledBuffer {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.buf
+    }
+}
+impl Debug for PooledBuffer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledBuffer").field("len", &self.buf.len()).finish()
+    }
+}
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        // Return the buffer if the pool is not full
+        let mut idle = self.pool.idle.lock().expect("pool lock is poisoned");
+        if idle.len() < self.pool.max_idle {
+            let buf = std::mem::take(&mut self.buf);
+            idle.push(buf);
+        }
+    }
+}
+
+/// A buffered reader that borrows its buffer from a [`BufferPool`]
+#[derive(Debug)]
+pub struct PooledReader<T> {
+    /// The underlying reader
+    inner: T,
+    /// The borrowed buffer
+    buf: PooledBuffer,
+    /// The position of the first unconsumed byte within the buffer
+    pos: usize,
+    /// The amount of valid bytes within the buffer
+    filled: usize,
+}
+impl<T> PooledReader<T> {
+    /// Creates a new buffered reader with a buffer borrowed from the given pool
+    pub fn new(inner: T, pool: &Arc<BufferPool>) -> Self {
+        Self { inner, buf: pool.take(), pos: 0, filled: 0 }
+    }
+
+    /// Returns the underlying reader and returns the buffer to the pool
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+impl<T> Read for PooledReader<T>
+where
+    T: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Bypass the buffer for large reads if the buffer is empty
+        if self.pos == self.filled && buf.len() >= self.buf.as_ref().len() {
+            return self.inner.read(buf);
+        }
+
+        // Copy from the buffer
+        let available = self.fill_buf()?;
+        let to_copy = available.len().min(buf.len());
+        buf[..to_copy].copy_from_slice(&available[..to_copy]);
+        self.consume(to_copy);
+        Ok(to_copy)
+    }
+}
+impl<T> BufRead for PooledReader<T>
+where
+    T: Read,
+{
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        // Refill the buffer if it has been consumed
+        if self.pos == self.filled {
+            self.filled = self.inner.read(self.buf.as_mut())?;
+            self.pos = 0;
+        }
+        Ok(&self.buf.as_ref()[self.pos..self.filled])
+    }
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.filled);
+    }
+}
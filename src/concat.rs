@@ -0,0 +1,197 @@
+//! Virtual concatenation of multiple segments as one representation
+
+use crate::positional::PositionalReader;
+use crate::representation::{self, Representation};
+use ehttpd::bytes::{Data, Source};
+use ehttpd::err;
+use ehttpd::error::Error;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// A segment of a concatenated representation
+#[derive(Debug, Clone)]
+pub enum Segment {
+    /// A range of a shared file
+    File {
+        /// The shared file
+        file: Arc<File>,
+        /// The range within the file
+        range: Range<u64>,
+    },
+    /// An in-memory chunk
+    Data(Data),
+    /// A run of a fixed byte
+    Fill {
+        /// The fill byte
+        byte: u8,
+        /// The amount of bytes
+        len: u64,
+    },
+}
+impl Segment {
+    /// The segment length
+    pub fn len(&self) -> u64 {
+        match self {
+            Self::File { range, .. } => range.end.saturating_sub(range.start),
+            Self::Data(data) => data.len() as u64,
+            Self::Fill { len, .. } => *len,
+        }
+    }
+    /// Whether the segment is empty or not
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Opens the given range relative to the segment start
+    fn open(&self, range: Range<u64>) -> Result<Source, Error> {
+        match self {
+            Self::File { file, range: file_range } => {
+                let range = (file_range.start + range.start)..(file_range.start + range.end);
+                let reader = PositionalReader::new(file.clone(), range);
+                Ok(Source::new(BufReader::new(reader)))
+            }
+            Self::Data(data) => {
+                let view =
+                    data.subcopy(range.start as usize..range.end as usize).expect("range would exceed segment size");
+                Ok(Source::from(view))
+            }
+            Self::Fill { byte, .. } => {
+                let reader = io::repeat(*byte).take(range.end - range.start);
+                Ok(Source::new(reader))
+            }
+        }
+    }
+}
+
+/// An ordered list of segments that is served as one contiguous representation
+#[derive(Debug, Clone)]
+pub struct Concat {
+    /// The segments
+    segments: Arc<[Segment]>,
+    /// The start offset of each segment within the whole
+    offsets: Arc<[u64]>,
+    /// The total length
+    len: u64,
+    /// The entity tag if any
+    etag: Option<Data>,
+    /// The content type if any
+    content_type: Option<Data>,
+}
+impl Concat {
+    /// Creates a new concatenation of the given segments
+    ///
+    /// # Note
+    /// This function raises an error if a file segment range is invalid or exceeds the current file size, since the
+    /// advertised length could not be delivered otherwise
+    pub fn new<I>(segments: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = Segment>,
+    {
+        // Compute the segment offsets
+        let segments: Arc<[Segment]> = segments.into_iter().collect();
+        let (mut offsets, mut len) = (Vec::with_capacity(segments.len()), 0u64);
+        for segment in segments.iter() {
+            // Validate file segments against the file size
+            if let Segment::File { file, range } = segment {
+                let file_size = representation::file_size(file, &file.metadata()?)?;
+                if range.start > range.end || range.end > file_size {
+                    return Err(err!("File segment would exceed file size"));
+                }
+            }
+
+            offsets.push(len);
+            len = len.checked_add(segment.len()).ok_or_else(|| err!("Concatenated length is too large"))?;
+        }
+
+        // Init self
+        Ok(Self { segments, offsets: offsets.into(), len, etag: None, content_type: None })
+    }
+    /// Sets the entity tag of the concatenation
+    pub fn with_etag(mut self, etag: Data) -> Self {
+        self.etag = Some(etag);
+        self
+    }
+    /// Sets the content type of the concatenation
+    pub fn with_content_type(mut self, content_type: Data) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// The segments
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+}
+impl Representation for Concat {
+    fn complete_length(&self) -> Result<Option<u64>, Error> {
+        Ok(Some(self.len))
+    }
+
+    fn etag(&self) -> Result<Option<Data>, Error> {
+        Ok(self.etag.clone())
+    }
+    fn content_type(&self) -> Option<Data> {
+        self.content_type.clone()
+    }
+
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error> {
+        // Validate the range
+        if range.start > range.end || range.end > self.len {
+            return Err(err!("Range would exceed concatenated size"));
+        }
+
+        // Find the first affected segment and map the range onto the segments
+        let first = self.offsets.partition_point(|offset| *offset <= range.start).saturating_sub(1);
+        let mut pending = VecDeque::new();
+        for (segment, offset) in self.segments.iter().zip(self.offsets.iter()).skip(first) {
+            // Stop after the last affected segment
+            if *offset >= range.end {
+                break;
+            }
+
+            // Clamp the range to the segment
+            let start = range.start.saturating_sub(*offset);
+            let end = (range.end - offset).min(segment.len());
+            if start < end {
+                pending.push_back((segment.clone(), start..end));
+            }
+        }
+        Ok(Source::new(ConcatReader { pending, current: None }))
+    }
+}
+
+/// A reader that streams across segment boundaries
+#[derive(Debug)]
+struct ConcatReader {
+    /// The pending segments and their relative ranges
+    pending: VecDeque<(Segment, Range<u64>)>,
+    /// The currently opened segment
+    current: Option<Source>,
+}
+impl Read for ConcatReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while !buf.is_empty() {
+            // Open the next segment if necessary
+            let current = match self.current.as_mut() {
+                Some(current) => current,
+                None => match self.pending.pop_front() {
+                    Some((segment, range)) => {
+                        let source = segment.open(range).map_err(|e| io::Error::other(e.to_string()))?;
+                        self.current.insert(source)
+                    }
+                    None => return Ok(0),
+                },
+            };
+
+            // Read from the current segment or advance to the next one
+            match current.read(buf)? {
+                0 => self.current = None,
+                read => return Ok(read),
+            }
+        }
+        Ok(0)
+    }
+}
@@ -0,0 +1,87 @@
+//! A seekable cursor over shared data
+
+use ehttpd::bytes::Data;
+use ehttpd::err;
+use ehttpd::error::Error;
+use std::io::{self, BufRead, ErrorKind, Read, Seek, SeekFrom};
+
+/// A `Read + Seek + BufRead` cursor over data that shares the underlying buffer instead of copying it
+#[derive(Debug, Clone)]
+pub struct DataCursor {
+    /// The underlying data
+    data: Data,
+    /// The current position
+    position: u64,
+}
+impl DataCursor {
+    /// Creates a new cursor at the start of the given data
+    pub fn new<T>(data: T) -> Self
+    where
+        T: Into<Data>,
+    {
+        Self { data: data.into(), position: 0 }
+    }
+
+    /// The current position
+    pub const fn position(&self) -> u64 {
+        self.position
+    }
+    /// The underlying data
+    pub const fn get_ref(&self) -> &Data {
+        &self.data
+    }
+    /// Returns the underlying data
+    pub fn into_inner(self) -> Data {
+        self.data
+    }
+
+    /// Reads the next `len` bytes as a view that shares the underlying buffer
+    ///
+    /// # Note
+    /// This function raises an error if there are less than `len` bytes remaining
+    pub fn read_view(&mut self, len: usize) -> Result<Data, Error> {
+        let start = self.offset();
+        let end = start.saturating_add(len);
+        let view = self.data.subcopy(start..end).ok_or_else(|| err!("Range would exceed data size"))?;
+        self.position += len as u64;
+        Ok(view)
+    }
+
+    /// The current position clamped to the data length
+    fn offset(&self) -> usize {
+        self.position.min(self.data.len() as u64) as usize
+    }
+}
+impl Read for DataCursor {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.fill_buf()?.read(buf)?;
+        self.consume(read);
+        Ok(read)
+    }
+}
+impl BufRead for DataCursor {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let offset = self.offset();
+        Ok(&self.data[offset..])
+    }
+    fn consume(&mut self, amt: usize) {
+        self.position += amt as u64;
+    }
+}
+impl Seek for DataCursor {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        // Compute the new position
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => (self.data.len() as u64).checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+
+        // Update the position
+        let Some(position) = position else {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position"));
+        };
+        self.position = position;
+        Ok(position)
+    }
+}
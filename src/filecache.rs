@@ -0,0 +1,286 @@
+//! A bounded cache of open file handles and their metadata
+
+use crate::positional::PositionalReader;
+use crate::representation::{self, Representation};
+use ehttpd::bytes::{Data, Source};
+use ehttpd::err;
+use ehttpd::error::Error;
+use std::collections::HashMap;
+use std::fs::{File, FileType};
+use std::io::BufReader;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// An open file handle together with a snapshot of its metadata and validators
+#[derive(Debug, Clone)]
+pub struct OpenFile {
+    /// The shared file handle
+    file: Arc<File>,
+    /// The file type
+    file_type: FileType,
+    /// The file length
+    len: u64,
+    /// The modification time if known
+    modified: Option<SystemTime>,
+    /// The entity tag if any
+    etag: Option<Data>,
+}
+impl OpenFile {
+    /// Opens the file at the given path and snapshots its metadata
+    pub fn open<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::open(path)?;
+        Self::new(file)
+    }
+    /// Snapshots the metadata of the given file
+    pub fn new<T>(file: T) -> Result<Self, Error>
+    where
+        T: Into<File>,
+    {
+        let file: File = file.into();
+        let metadata = file.metadata()?;
+        let len = representation::file_size(&file, &metadata)?;
+        let etag = representation::file_etag(&metadata);
+        Ok(Self {
+            file: Arc::new(file),
+            file_type: metadata.file_type(),
+            len,
+            modified: metadata.modified().ok(),
+            etag,
+        })
+    }
+
+    /// The shared file handle
+    pub fn file(&self) -> &Arc<File> {
+        &self.file
+    }
+    /// The file type
+    pub fn file_type(&self) -> FileType {
+        self.file_type
+    }
+}
+impl Representation for OpenFile {
+    fn complete_length(&self) -> Result<Option<u64>, Error> {
+        Ok(Some(self.len))
+    }
+
+    fn etag(&self) -> Result<Option<Data>, Error> {
+        Ok(self.etag.clone())
+    }
+    fn last_modified(&self) -> Result<Option<SystemTime>, Error> {
+        Ok(self.modified)
+    }
+
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error> {
+        // Validate the range against the snapshot
+        if range.start > range.end || range.end > self.len {
+            return Err(err!("Range would exceed file size"));
+        }
+
+        // Buffer the reader
+        let reader = PositionalReader::new(self.file.clone(), range);
+        let reader = BufReader::new(reader);
+        Ok(Source::new(reader))
+    }
+}
+
+/// A cache entry
+#[derive(Debug)]
+struct CacheEntry {
+    /// The cached file
+    file: OpenFile,
+    /// When the entry has been created
+    created: Instant,
+    /// When the entry has been used for the last time
+    used: Instant,
+}
+
+/// A file that is currently being opened without holding the cache lock
+#[derive(Debug)]
+struct PendingLoad {
+    /// The amount of concurrent loaders for the path
+    loaders: usize,
+    /// The invalidation generation, which is bumped if the path is invalidated while it is being opened
+    generation: u64,
+}
+
+/// The lock-protected cache state
+#[derive(Debug, Default)]
+struct CacheState {
+    /// The cache entries
+    entries: HashMap<PathBuf, CacheEntry>,
+    /// The files that are currently being opened
+    loads: HashMap<PathBuf, PendingLoad>,
+}
+impl CacheState {
+    /// Registers a new load for the given path and returns the current generation
+    fn begin_load(&mut self, path: &Path) -> u64 {
+        let load = self.loads.entry(path.to_path_buf()).or_insert(PendingLoad { loaders: 0, generation: 0 });
+        load.loaders += 1;
+        load.generation
+    }
+    /// Unregisters a load for the given path and returns whether the path has not been invalidated in the meantime
+    fn end_load(&mut self, path: &Path, generation: u64) -> bool {
+        let Some(load) = self.loads.get_mut(path) else {
+            unreachable!("load has not been registered");
+        };
+
+        // Remove the load if this was the last loader
+        let is_fresh = load.generation == generation;
+        load.loaders -= 1;
+        if load.loaders == 0 {
+            self.loads.remove(path);
+        }
+        is_fresh
+    }
+    /// Invalidates all entries and pending loads that match the predicate
+    fn invalidate<F>(&mut self, predicate: F) -> bool
+    where
+        F: Fn(&Path) -> bool,
+    {
+        // Bump the generation of all matching pending loads, so that they are not cached once they complete
+        for (_, load) in self.loads.iter_mut().filter(|(path, _)| predicate(path)) {
+            load.generation += 1;
+        }
+
+        // Remove the entries
+        let len = self.entries.len();
+        self.entries.retain(|path, _| !predicate(path));
+        self.entries.len() != len
+    }
+}
+
+/// A bounded cache of open file handles and their metadata, keyed by path
+///
+/// # Eviction
+/// Entries expire after the configured time-to-live. If the cache is full, expired entries are purged first, and then
+/// the least recently used entry is evicted.
+///
+/// # Invalidation
+/// Files are opened without holding the cache lock. If a path is invalidated while it is being opened, the opened file
+/// is returned to the caller but not cached, so that a stale snapshot never outlives the invalidation.
+#[derive(Debug)]
+pub struct FileCache {
+    /// The cache state
+    state: Mutex<CacheState>,
+    /// The maximum amount of entries
+    capacity: usize,
+    /// The time-to-live of an entry
+    ttl: Duration,
+}
+impl FileCache {
+    /// Creates a new file cache with the given capacity and time-to-live
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self { state: Mutex::default(), capacity, ttl }
+    }
+
+    /// Gets the cached file for the given path, or opens and caches the file if necessary
+    pub fn get<P>(&self, path: P) -> Result<OpenFile, Error>
+    where
+        P: AsRef<Path>,
+    {
+        self.get_with(path, |path| OpenFile::open(path))
+    }
+    /// Gets the cached file for the given path, or opens the file with `open` and caches it if necessary
+    pub fn get_with<P, F>(&self, path: P, open: F) -> Result<OpenFile, Error>
+    where
+        P: AsRef<Path>,
+        F: FnOnce(&Path) -> Result<OpenFile, Error>,
+    {
+        // Return the cached file if it is still valid, or register the load otherwise
+        let path = path.as_ref();
+        let now = Instant::now();
+        let generation = {
+            let mut state = self.state.lock().expect("cache lock is poisoned");
+            if let Some(file) = self.get_cached(&mut state, path, now) {
+                return Ok(file);
+            }
+            state.begin_load(path)
+        };
+
+        // Open the file
+        // Note: The file is opened without holding the lock, so a concurrent lookup may open the same file twice
+        let file = open(path);
+
+        // Cache the file unless the path has been invalidated in the meantime
+        let mut state = self.state.lock().expect("cache lock is poisoned");
+        let is_fresh = state.end_load(path, generation);
+        let file = file?;
+        if is_fresh {
+            self.insert(&mut state, path.to_path_buf(), file.clone(), now);
+        }
+        Ok(file)
+    }
+
+    /// Checks whether the cache contains an entry for the given path
+    pub fn contains<P>(&self, path: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        let state = self.state.lock().expect("cache lock is poisoned");
+        state.entries.contains_key(path.as_ref())
+    }
+
+    /// Invalidates the cache entry for the given path and returns whether an entry existed
+    pub fn invalidate<P>(&self, path: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        let mut state = self.state.lock().expect("cache lock is poisoned");
+        state.invalidate(|candidate| candidate == path.as_ref())
+    }
+    /// Invalidates all cache entries below the given directory
+    pub fn invalidate_dir<P>(&self, dir: P)
+    where
+        P: AsRef<Path>,
+    {
+        let mut state = self.state.lock().expect("cache lock is poisoned");
+        state.invalidate(|path| path.starts_with(dir.as_ref()));
+    }
+    /// Invalidates all cache entries
+    pub fn clear(&self) {
+        let mut state = self.state.lock().expect("cache lock is poisoned");
+        state.invalidate(|_| true);
+    }
+
+    /// Gets the cached file for the given path if it exists and is not expired
+    fn get_cached(&self, state: &mut CacheState, path: &Path, now: Instant) -> Option<OpenFile> {
+        let entry = state.entries.get_mut(path)?;
+
+        // Check if the entry has expired
+        if now.saturating_duration_since(entry.created) >= self.ttl {
+            state.entries.remove(path);
+            return None;
+        }
+
+        // Update the usage time
+        entry.used = now;
+        Some(entry.file.clone())
+    }
+    /// Inserts a new cache entry and evicts old entries if necessary
+    fn insert(&self, state: &mut CacheState, path: PathBuf, file: OpenFile, now: Instant) {
+        // A zero-capacity cache does not cache anything
+        if self.capacity == 0 {
+            return;
+        }
+
+        // Purge expired entries and evict the least recently used entry if necessary
+        let entries = &mut state.entries;
+        if entries.len() >= self.capacity && !entries.contains_key(&path) {
+            entries.retain(|_, entry| now.saturating_duration_since(entry.created) < self.ttl);
+        }
+        if entries.len() >= self.capacity && !entries.contains_key(&path) {
+            let least_recently_used = entries.iter().min_by_key(|(_, entry)| entry.used).map(|(path, _)| path.clone());
+            if let Some(least_recently_used) = least_recently_used {
+                entries.remove(&least_recently_used);
+            }
+        }
+
+        // Insert the entry
+        entries.insert(path, CacheEntry { file, created: now, used: now });
+    }
+}
@@ -0,0 +1,125 @@
+//! Range sources over forward-only streams that skip to the requested offsets
+
+use crate::anyrange::AnyInclusiveRange;
+use ehttpd::err;
+use ehttpd::error::Error;
+use std::io::{self, ErrorKind, Read};
+use std::ops::Range;
+
+/// A forward-only stream with an optional known length
+#[derive(Debug)]
+pub struct ForwardSource<T> {
+    /// The underlying stream
+    reader: T,
+    /// The stream length if known
+    len: Option<u64>,
+}
+impl<T> ForwardSource<T>
+where
+    T: Read,
+{
+    /// Creates a new forward-only source with the given length if known
+    pub const fn new(reader: T, len: Option<u64>) -> Self {
+        Self { reader, len }
+    }
+
+    /// The stream length if known
+    pub const fn stream_len(&self) -> Option<u64> {
+        self.len
+    }
+
+    /// Resolves the requested range to an absolute byte range
+    ///
+    /// # Note
+    /// Suffix ranges (`bytes=-N`, parsed as [`AnyInclusiveRange::To`]) and open-ended ranges require a known length and
+    /// are refused otherwise
+    pub fn resolve(&self, range: AnyInclusiveRange<u64>) -> Result<Range<u64>, Error> {
+        let (start, end_incl) = match (range, self.len) {
+            (AnyInclusiveRange::FromTo { start, end }, _) => (start, end),
+            (_, None) => return Err(err!("Range requires a known stream length")),
+            (_, Some(0)) => return Err(err!("Range is not satisfiable for an empty stream")),
+            (AnyInclusiveRange::To { end: 0 }, _) => return Err(err!("Suffix range is empty")),
+            // Note: Suffix ranges select the last `N` bytes and are clamped to the stream length
+            (AnyInclusiveRange::To { end: suffix }, Some(len)) => (len.saturating_sub(suffix), len - 1),
+            (range, Some(len)) => {
+                let range = range.to_inclusive(0, len - 1)?;
+                (*range.start(), *range.end())
+            }
+        };
+
+        // Validate the range
+        if start > end_incl {
+            return Err(err!("End of inclusive range is before start"));
+        }
+        if self.len.is_some_and(|len| end_incl >= len) {
+            return Err(err!("Range would exceed stream length"));
+        }
+        Ok(start..end_incl.saturating_add(1))
+    }
+
+    /// Creates a reader that skips to the start of the given range and yields exactly the range
+    pub fn into_range(self, range: Range<u64>) -> ForwardRanges<T> {
+        ForwardRanges { reader: self.reader, position: 0, ranges: vec![range], next: 0 }
+    }
+    /// Creates a reader that yields the given ranges in order
+    ///
+    /// # Note
+    /// Since the stream cannot seek backwards, the ranges must be ascending and must not overlap
+    pub fn into_ranges(self, ranges: &[Range<u64>]) -> Result<ForwardRanges<T>, Error> {
+        // Validate the range order
+        let ascending = ranges.windows(2).all(|pair| pair[0].end <= pair[1].start);
+        if !ascending {
+            return Err(err!("Ranges over forward-only streams must be ascending and must not overlap"));
+        }
+        Ok(ForwardRanges { reader: self.reader, position: 0, ranges: ranges.to_vec(), next: 0 })
+    }
+}
+
+/// A reader that yields one or more ascending ranges of a forward-only stream by discarding the bytes in between
+#[derive(Debug)]
+pub struct ForwardRanges<T> {
+    /// The underlying stream
+    reader: T,
+    /// The current position within the stream
+    position: u64,
+    /// The ranges to yield
+    ranges: Vec<Range<u64>>,
+    /// The index of the current range
+    next: usize,
+}
+impl<T> Read for ForwardRanges<T>
+where
+    T: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Get the current range and skip exhausted ranges
+        let range = loop {
+            let Some(range) = self.ranges.get(self.next) else {
+                return Ok(0);
+            };
+            match range.start < range.end && self.position < range.end {
+                true => break range.clone(),
+                false => self.next += 1,
+            }
+        };
+
+        // Discard all bytes before the range
+        if self.position < range.start {
+            let to_skip = range.start - self.position;
+            let skipped = io::copy(&mut (&mut self.reader).take(to_skip), &mut io::sink())?;
+            self.position += skipped;
+            if skipped < to_skip {
+                return Err(io::Error::new(ErrorKind::UnexpectedEof, "stream is shorter than the requested range"));
+            }
+        }
+
+        // Read the next chunk of the range
+        let to_read = (buf.len() as u64).min(range.end - self.position) as usize;
+        let read = self.reader.read(&mut buf[..to_read])?;
+        if read == 0 && to_read > 0 {
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, "stream is shorter than the requested range"));
+        }
+        self.position += read as u64;
+        Ok(read)
+    }
+}
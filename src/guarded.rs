@@ -0,0 +1,126 @@
+//! A file source that detects changes of the file while a range is being served
+
+use crate::advice::FileReader;
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+use std::fs::{File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::time::SystemTime;
+
+/// A snapshot of the state of a file
+///
+/// # Note
+/// The snapshot is compared against the open file handle, which always refers to the same inode; so if the file is
+/// replaced or deleted via its path, the handle still serves the original content consistently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileSnapshot {
+    /// The file length
+    pub len: u64,
+    /// The modification time if known
+    pub modified: Option<SystemTime>,
+}
+impl FileSnapshot {
+    /// Snapshots the given file metadata
+    pub fn new(metadata: &Metadata) -> Self {
+        Self { len: metadata.len(), modified: metadata.modified().ok() }
+    }
+
+    /// Compares the snapshot with the current state of the file
+    ///
+    /// # Note
+    /// If the file metadata cannot be queried, the underlying I/O error is returned as-is.
+    pub fn check(&self, file: &File) -> io::Result<()> {
+        let current = Self::new(&file.metadata()?);
+        if current.len < self.len {
+            return Err(FileChangedError::Truncated.into());
+        }
+        if current.len != self.len || current.modified != self.modified {
+            return Err(FileChangedError::Modified.into());
+        }
+        Ok(())
+    }
+}
+
+/// The error that aborts a guarded body if the file changes while it is being served
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileChangedError {
+    /// The file has been truncated
+    Truncated,
+    /// The file has been modified
+    Modified,
+}
+impl FileChangedError {
+    /// Gets the file change error from the given I/O error if any
+    pub fn from_io_error(error: &io::Error) -> Option<Self> {
+        let inner = error.get_ref()?;
+        inner.downcast_ref::<Self>().copied()
+    }
+}
+impl Display for FileChangedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "File has been truncated while it was being served"),
+            Self::Modified => write!(f, "File has been modified while it was being served"),
+        }
+    }
+}
+impl StdError for FileChangedError {
+    // No members to implement
+}
+impl From<FileChangedError> for io::Error {
+    fn from(value: FileChangedError) -> Self {
+        io::Error::other(value)
+    }
+}
+
+/// A reader over a file range that aborts with a [`FileChangedError`] if the file changes while it is being read
+///
+/// # Note
+/// The file is re-validated every `check_interval` bytes, before the last chunk of the range is returned, and if the
+/// file ends early; so a body either completes with consistent content or fails instead of silently returning short or
+/// mixed data.
+#[derive(Debug)]
+pub struct GuardedReader {
+    /// The virtually truncated file
+    file: io::Take<File>,
+    /// The snapshot to compare against
+    snapshot: FileSnapshot,
+    /// The amount of bytes after which the file is re-validated
+    check_interval: u64,
+    /// The amount of bytes read since the last validation
+    unchecked: u64,
+}
+impl GuardedReader {
+    /// Creates a new guarded reader over the given range that re-validates the file against the snapshot every
+    /// `check_interval` bytes
+    pub fn new(mut file: File, snapshot: FileSnapshot, range: Range<u64>, check_interval: u64) -> io::Result<Self> {
+        file.seek(SeekFrom::Start(range.start))?;
+        let file = file.take(range.end.saturating_sub(range.start));
+        Ok(Self { file, snapshot, check_interval, unchecked: 0 })
+    }
+}
+impl Read for GuardedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Read the next chunk and detect early ends
+        let remaining = self.file.limit();
+        let read = self.file.read(buf)?;
+        if read == 0 && remaining > 0 {
+            self.snapshot.check(self.file.get_ref())?;
+            return Err(FileChangedError::Truncated.into());
+        }
+
+        // Re-validate the file periodically and before the last chunk is returned
+        self.unchecked = self.unchecked.saturating_add(read as u64);
+        if self.unchecked >= self.check_interval || (read > 0 && self.file.limit() == 0) {
+            self.snapshot.check(self.file.get_ref())?;
+            self.unchecked = 0;
+        }
+        Ok(read)
+    }
+}
+impl FileReader for GuardedReader {
+    fn file(&self) -> &File {
+        self.file.get_ref()
+    }
+}
@@ -0,0 +1,105 @@
+//! A request handler that serves (ranged) objects from a backend
+
+use crate::anyrange::AnyInclusiveRange;
+use crate::backend::Backend;
+use crate::rangerequest::RangeRequest;
+use crate::rangeresponse::{self, RangeResponse};
+use crate::representation::Representation;
+use ehttpd::err;
+use ehttpd::error::Error;
+use ehttpd::http::{Request, Response};
+
+/// Serves the object referenced by the request target from the given backend
+///
+/// # Note
+/// The request target is used as object key (without leading `/` and without the query string). This function
+/// answers `GET` and `HEAD` requests with `200 OK` or `206 Partial Content` responses, and maps all failures to an
+/// appropriate error response. Invalid `Range` header fields are ignored.
+pub fn serve<B, const HEADER_SIZE_MAX: usize>(backend: &B, request: &Request<'_, HEADER_SIZE_MAX>) -> Response
+where
+    B: Backend + ?Sized,
+{
+    // Validate the method
+    let is_head = match request.method.as_ref() {
+        b"GET" => false,
+        b"HEAD" => true,
+        _ => return Response::new_405_methodnotallowed(),
+    };
+
+    // Get the object key
+    let Ok(target) = str::from_utf8(&request.target) else {
+        return Response::new_400_badrequest();
+    };
+    let target = target.split_once('?').map(|(path, _)| path).unwrap_or(target);
+    let key = target.trim_start_matches('/');
+
+    // Lookup the object and build the response
+    let object = match backend.lookup(key) {
+        Ok(Some(object)) => object,
+        Ok(None) => return Response::new_404_notfound(),
+        Err(_) => return Response::new_500_internalservererror(),
+    };
+    let mut response = match respond(&object, request) {
+        Ok(response) => response,
+        Err(_) => return Response::new_500_internalservererror(),
+    };
+
+    // Strip the body for HEAD requests
+    if is_head {
+        response.make_head();
+    }
+    response
+}
+
+/// Creates the response for the given object
+fn respond<T, const HEADER_SIZE_MAX: usize>(
+    object: &T,
+    request: &Request<'_, HEADER_SIZE_MAX>,
+) -> Result<Response, Error>
+where
+    T: Representation + ?Sized,
+{
+    // Serve the entire object if there is no valid range
+    let total = (object.complete_length()?).ok_or_else(|| err!("Complete length of representation is unknown"))?;
+    let Ok(Some(range)) = request.range() else {
+        return respond_full(object, total);
+    };
+
+    // Resolve the range and clamp the end to the object size
+    // Note: `bytes=-N` is a suffix range that selects the last `N` bytes (RFC 9110, section 14.1.2)
+    let last = total.saturating_sub(1);
+    let range = match range {
+        AnyInclusiveRange::To { end: suffix } if total > 0 && suffix > 0 => total.saturating_sub(suffix)..=last,
+        AnyInclusiveRange::To { .. } => return Ok(respond_unsatisfiable(total)),
+        range => match range.to_inclusive(0, last) {
+            Ok(range) if total > 0 && *range.start() <= last => *range.start()..=last.min(*range.end()),
+            _ => return Ok(respond_unsatisfiable(total)),
+        },
+    };
+
+    // Create the partial response
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_accept_ranges_bytes();
+    response.set_body_range(object, range)?;
+    Ok(response)
+}
+
+/// Creates a `200 OK` response with the entire object
+fn respond_full<T>(object: &T, total: u64) -> Result<Response, Error>
+where
+    T: Representation + ?Sized,
+{
+    let mut response = Response::new_200_ok();
+    response.set_accept_ranges_bytes();
+    response.set_content_length(total);
+    rangeresponse::set_representation_fields(&mut response, object)?;
+    response.body = object.open_range(0..total)?;
+    Ok(response)
+}
+
+/// Creates a `416 Range Not Satisfiable` response for an object with the given size
+fn respond_unsatisfiable(total: u64) -> Response {
+    let mut response = Response::new_416_rangenotsatisfiable();
+    response.set_field("Content-Range", format!("bytes */{total}"));
+    response
+}
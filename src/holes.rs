@@ -0,0 +1,120 @@
+//! Hole detection in sparse files via `SEEK_DATA`/`SEEK_HOLE`
+
+use crate::advice::FileReader;
+use crate::positional;
+use std::fs::File;
+use std::io::{self, ErrorKind, Read};
+use std::ops::Range;
+use std::os::fd::AsRawFd;
+
+/// A region of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Region {
+    /// A data region up to the given offset
+    Data(u64),
+    /// A hole up to the given offset
+    Hole(u64),
+}
+
+/// A reader over a file range that synthesizes zeros for holes instead of reading them from disk
+///
+/// # Note
+/// If the filesystem does not support hole detection, the entire range is treated as data.
+#[derive(Debug)]
+pub struct HoleReader {
+    /// The underlying file
+    file: File,
+    /// The current position within the file
+    position: u64,
+    /// The end of the range within the file
+    end: u64,
+    /// The region at the current position if it has been probed already
+    region: Option<Region>,
+}
+impl HoleReader {
+    /// Creates a new hole-skipping reader over the given range of the file
+    pub fn new(file: File, range: Range<u64>) -> Self {
+        Self { file, position: range.start, end: range.end.max(range.start), region: None }
+    }
+
+    /// Probes the region at the current position
+    fn probe(&self) -> io::Result<Region> {
+        // Find the next data region
+        let data = match seek(&self.file, self.position, libc::SEEK_DATA) {
+            Ok(data) => data,
+            // Note: `ENXIO` means that there is no more data after the position, or that the file has been truncated
+            Err(e) if e.raw_os_error() == Some(libc::ENXIO) => match self.file.metadata()?.len() < self.end {
+                true => return Err(io::Error::new(ErrorKind::UnexpectedEof, "file has been truncated")),
+                false => return Ok(Region::Hole(self.end)),
+            },
+            // Note: `EINVAL` means that the filesystem does not support hole detection
+            Err(e) if e.raw_os_error() == Some(libc::EINVAL) => return Ok(Region::Data(self.end)),
+            Err(e) => return Err(e),
+        };
+        if data > self.position {
+            return Ok(Region::Hole(data.min(self.end)));
+        }
+
+        // Find the end of the data region
+        match seek(&self.file, self.position, libc::SEEK_HOLE) {
+            Ok(hole) => Ok(Region::Data(hole.min(self.end))),
+            Err(e) if e.raw_os_error() == Some(libc::ENXIO) => Ok(Region::Data(self.end)),
+            Err(e) => Err(e),
+        }
+    }
+}
+impl Read for HoleReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Check if we are done
+        if self.position >= self.end || buf.is_empty() {
+            return Ok(0);
+        }
+
+        // Probe the region at the current position if necessary
+        let region = match self.region {
+            Some(Region::Data(end) | Region::Hole(end)) if self.position < end => self.region,
+            _ => None,
+        };
+        let region = match region {
+            Some(region) => region,
+            None => *self.region.insert(self.probe()?),
+        };
+
+        // Read data or synthesize zeros
+        let read = match region {
+            Region::Data(end) => {
+                let to_read = (buf.len() as u64).min(end - self.position) as usize;
+                let read = positional::read_at(&self.file, &mut buf[..to_read], self.position)?;
+                if read == 0 {
+                    return Err(io::Error::new(ErrorKind::UnexpectedEof, "file has been truncated"));
+                }
+                read
+            }
+            Region::Hole(end) => {
+                let to_fill = (buf.len() as u64).min(end - self.position) as usize;
+                buf[..to_fill].fill(0);
+                to_fill
+            }
+        };
+
+        // Advance the position
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+impl FileReader for HoleReader {
+    fn file(&self) -> &File {
+        &self.file
+    }
+}
+
+/// Seeks to the next data or hole at or after the given offset
+fn seek(file: &File, offset: u64, whence: libc::c_int) -> io::Result<u64> {
+    let offset = libc::off_t::try_from(offset).map_err(io::Error::other)?;
+
+    // SAFETY: `lseek` has no memory safety preconditions
+    match unsafe { libc::lseek(file.as_raw_fd(), offset, whence) } {
+        -1 => Err(io::Error::last_os_error()),
+        offset => Ok(offset as u64),
+    }
+}
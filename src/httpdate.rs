@@ -0,0 +1,38 @@
+//! Formatting of HTTP dates
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The abbreviated weekday names, starting with Thursday as the weekday of the UNIX epoch
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+/// The abbreviated month names
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// Formats the given time as IMF-fixdate (e.g. `Sun, 06 Nov 1994 08:49:37 GMT`)
+///
+/// # Note
+/// Times before the UNIX epoch are clamped to the UNIX epoch
+pub fn format(time: SystemTime) -> String {
+    // Split the timestamp into days and seconds
+    let timestamp = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let (days, secs) = (timestamp / 86400, timestamp % 86400);
+    let weekday = WEEKDAYS[(days % 7) as usize];
+
+    // Convert the days into a civil date (see http://howardhinnant.github.io/date_algorithms.html#civil_from_days)
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = match month_index < 10 {
+        true => month_index + 3,
+        false => month_index - 9,
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    // Format the date
+    let (hour, minute, second) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    let month = MONTHS[(month - 1) as usize];
+    format!("{weekday}, {day:02} {month} {year} {hour:02}:{minute:02}:{second:02} GMT")
+}
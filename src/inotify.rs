@@ -0,0 +1,197 @@
+//! An inotify-driven watcher that invalidates file cache entries on changes
+
+use crate::filecache::FileCache;
+use ehttpd::err;
+use ehttpd::error::Error;
+use std::collections::HashMap;
+use std::ffi::{CString, OsStr};
+use std::io::{self, ErrorKind};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::{fs, mem, ptr};
+
+/// The events that invalidate a cache entry
+const WATCH_MASK: u32 = libc::IN_MODIFY
+    | libc::IN_ATTRIB
+    | libc::IN_CLOSE_WRITE
+    | libc::IN_CREATE
+    | libc::IN_DELETE
+    | libc::IN_MOVED_FROM
+    | libc::IN_MOVED_TO
+    | libc::IN_DELETE_SELF
+    | libc::IN_MOVE_SELF;
+/// The poll timeout in milliseconds after which the watcher thread checks whether it should stop
+const POLL_TIMEOUT_MS: libc::c_int = 100;
+
+/// The state shared between the watcher and its thread
+#[derive(Debug)]
+struct Shared {
+    /// The inotify instance
+    fd: OwnedFd,
+    /// The watched directories by watch descriptor
+    dirs: Mutex<HashMap<libc::c_int, PathBuf>>,
+    /// The cache to invalidate
+    cache: Arc<FileCache>,
+    /// Whether the watcher thread should stop
+    stop: AtomicBool,
+}
+impl Shared {
+    /// Processes inotify events until the watcher is stopped
+    fn run(&self) {
+        // Note: The buffer is aligned for `inotify_event` and large enough for at least one event with a maximum name
+        let mut buf = [0u64; 1024];
+        while !self.stop.load(Ordering::Relaxed) {
+            // Wait for events
+            let mut pollfd = libc::pollfd { fd: self.fd.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+            // SAFETY: `pollfd` is a valid pointer to exactly one `pollfd` struct
+            let ready = unsafe { libc::poll(&mut pollfd, 1, POLL_TIMEOUT_MS) };
+            if ready <= 0 {
+                continue;
+            }
+
+            // Read the events
+            // SAFETY: The buffer is valid for writes of `size_of_val(&buf)` bytes
+            let read = unsafe { libc::read(self.fd.as_raw_fd(), buf.as_mut_ptr().cast(), mem::size_of_val(&buf)) };
+            match usize::try_from(read) {
+                Ok(read) => self.process(&buf, read),
+                Err(_) if io::Error::last_os_error().kind() == ErrorKind::Interrupted => continue,
+                Err(_) if io::Error::last_os_error().kind() == ErrorKind::WouldBlock => continue,
+                Err(_) => {
+                    // The inotify instance is unusable, so we cannot guarantee that the cache is fresh anymore
+                    self.cache.clear();
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Processes the events within the first `len` bytes of `buf`
+    fn process(&self, buf: &[u64], len: usize) {
+        // SAFETY: The buffer is valid for reads of at least `len` bytes
+        let bytes = unsafe { std::slice::from_raw_parts(buf.as_ptr().cast::<u8>(), len) };
+        let mut offset = 0;
+        while offset + mem::size_of::<libc::inotify_event>() <= bytes.len() {
+            // SAFETY: The kernel has written a complete event header at the given offset
+            let event: libc::inotify_event = unsafe { ptr::read_unaligned(bytes[offset..].as_ptr().cast()) };
+            let name_start = offset + mem::size_of::<libc::inotify_event>();
+            let name_end = (name_start + event.len as usize).min(bytes.len());
+            offset = name_end;
+
+            // Get the name without the trailing NUL padding
+            let name = &bytes[name_start..name_end];
+            let name = &name[..name.iter().position(|byte| *byte == 0).unwrap_or(name.len())];
+            self.handle(&event, OsStr::from_bytes(name));
+        }
+    }
+
+    /// Handles a single event
+    fn handle(&self, event: &libc::inotify_event, name: &OsStr) {
+        // Drop everything if events have been lost
+        if event.mask & libc::IN_Q_OVERFLOW != 0 {
+            self.cache.clear();
+            return;
+        }
+
+        // Get the affected directory
+        let mut dirs = self.dirs.lock().expect("watcher lock is poisoned");
+        let Some(dir) = dirs.get(&event.wd) else {
+            return;
+        };
+
+        // Invalidate the affected entries
+        match name.is_empty() {
+            true => self.cache.invalidate_dir(dir),
+            false => self.cache.invalidate_dir(dir.join(name)),
+        }
+
+        // Forget removed watches
+        if event.mask & libc::IN_IGNORED != 0 {
+            dirs.remove(&event.wd);
+        }
+    }
+}
+
+/// A watcher that subscribes to inotify events for served directories and evicts affected file cache entries
+///
+/// # Note
+/// Watches are not recursive; use [`Self::watch_recursive`] to watch existing subdirectories too. Directories that are
+/// created after they have been watched are not watched automatically.
+#[derive(Debug)]
+pub struct CacheWatcher {
+    /// The shared state
+    shared: Arc<Shared>,
+    /// The watcher thread
+    thread: Option<JoinHandle<()>>,
+}
+impl CacheWatcher {
+    /// Creates a new watcher that invalidates entries of the given cache
+    pub fn new(cache: Arc<FileCache>) -> Result<Self, Error> {
+        // Create the inotify instance
+        // SAFETY: `inotify_init1` has no memory safety preconditions
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(err!(with: io::Error::last_os_error(), "Failed to create inotify instance"));
+        }
+
+        // SAFETY: The file descriptor is valid and exclusively owned by us
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        let shared = Arc::new(Shared { fd, dirs: Mutex::default(), cache, stop: AtomicBool::new(false) });
+
+        // Spawn the watcher thread
+        let thread_shared = shared.clone();
+        let thread = thread::spawn(move || thread_shared.run());
+        Ok(Self { shared, thread: Some(thread) })
+    }
+
+    /// Watches the given directory
+    pub fn watch<P>(&self, dir: P) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        // Note: The path is not canonicalized, since it must match the paths used for the cache lookups
+        let dir = dir.as_ref();
+        let path = CString::new(dir.as_os_str().as_bytes()).map_err(|e| err!(with: e, "Invalid directory path"))?;
+
+        // Add the watch and register the directory
+        // Note: The lock is held until the directory is registered, so no event for the new watch can be dropped
+        let mut dirs = self.shared.dirs.lock().expect("watcher lock is poisoned");
+        // SAFETY: The file descriptor is valid and `path` is a valid NUL-terminated string
+        let wd = unsafe { libc::inotify_add_watch(self.shared.fd.as_raw_fd(), path.as_ptr(), WATCH_MASK) };
+        if wd < 0 {
+            return Err(err!(with: io::Error::last_os_error(), "Failed to watch directory"));
+        }
+        dirs.insert(wd, dir.to_path_buf());
+        Ok(())
+    }
+    /// Watches the given directory and all of its existing subdirectories
+    pub fn watch_recursive<P>(&self, dir: P) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        // Watch the directory itself
+        let dir = dir.as_ref();
+        self.watch(dir)?;
+
+        // Watch the subdirectories
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                self.watch_recursive(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+}
+impl Drop for CacheWatcher {
+    fn drop(&mut self) {
+        // Stop the watcher thread
+        self.shared.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
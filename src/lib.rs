@@ -1,11 +1,38 @@
 #![doc = include_str!("../README.md")]
 
+pub mod advice;
 pub mod anyrange;
+pub mod backend;
+pub mod bufpool;
+pub mod concat;
+pub mod cursor;
+pub mod filecache;
+pub mod forward;
+pub mod guarded;
+pub mod handler;
+#[cfg(all(target_os = "linux", feature = "seekhole"))]
+pub mod holes;
+mod httpdate;
+#[cfg(all(target_os = "linux", feature = "inotify"))]
+pub mod inotify;
+#[cfg(all(unix, feature = "mmap"))]
+pub mod mmap;
+pub mod positional;
 pub mod rangeext;
 mod rangerequest;
 mod rangeresponse;
+pub mod readplan;
+mod representation;
+#[cfg(all(target_os = "linux", feature = "sendfile"))]
+pub mod sendfile;
+pub mod sparse;
+pub mod spool;
+pub mod synthetic;
+pub mod tail;
+pub mod transform;
 
 pub use crate::rangerequest::RangeRequest;
-pub use crate::rangeresponse::RangeResponse;
+pub use crate::rangeresponse::{FileRangeOptions, RangeResponse};
+pub use crate::representation::Representation;
 // Re-export our ehttpd dependency
 pub use ehttpd;
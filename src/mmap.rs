@@ -0,0 +1,177 @@
+//! Memory-mapped file representations
+
+use crate::representation::{self, Representation};
+use ehttpd::bytes::{Data, Source};
+use ehttpd::err;
+use ehttpd::error::Error;
+use std::fmt::{self, Debug, Formatter};
+use std::fs::File;
+use std::io::{self, Read};
+use std::ops::{Deref, Range};
+use std::os::fd::AsRawFd;
+use std::ptr::{self, NonNull};
+use std::slice;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// A read-only memory mapping
+struct Mapping {
+    /// The mapped memory
+    ptr: NonNull<libc::c_void>,
+    /// The length of the mapped memory
+    len: usize,
+}
+impl Mapping {
+    /// Maps the first `len` bytes of the given file read-only
+    fn new(file: &File, len: usize) -> Result<Self, Error> {
+        // Note: Zero-length mappings are invalid
+        if len == 0 {
+            return Err(err!("Cannot map empty file"));
+        }
+
+        // SAFETY: We create a new private read-only mapping which does not alias any Rust-managed memory
+        let ptr = unsafe { libc::mmap(ptr::null_mut(), len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0) };
+        match NonNull::new(ptr) {
+            Some(ptr) if ptr.as_ptr() != libc::MAP_FAILED => Ok(Self { ptr, len }),
+            _ => Err(err!(with: io::Error::last_os_error(), "Failed to map file")),
+        }
+    }
+}
+impl Deref for Mapping {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: The mapping is valid and readable for `len` bytes until it is dropped
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr().cast(), self.len) }
+    }
+}
+impl Debug for Mapping {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mapping").field("ptr", &self.ptr).field("len", &self.len).finish()
+    }
+}
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        // SAFETY: The mapping was created by `mmap` with the given length and is not referenced anymore
+        unsafe { libc::munmap(self.ptr.as_ptr(), self.len) };
+    }
+}
+// SAFETY: The mapping is read-only and can be shared across threads
+unsafe impl Send for Mapping {}
+// SAFETY: The mapping is read-only and can be shared across threads
+unsafe impl Sync for Mapping {}
+
+/// A reader over a range of a memory mapping
+#[derive(Debug)]
+struct MappingRange {
+    /// The underlying mapping
+    mapping: Arc<Mapping>,
+    /// The current position within the mapping
+    pos: usize,
+    /// The end of the range within the mapping
+    end: usize,
+}
+impl Read for MappingRange {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Get the remaining bytes
+        let remaining = self.mapping.get(self.pos..self.end).unwrap_or_default();
+        let to_copy = remaining.len().min(buf.len());
+
+        // Copy the bytes
+        buf[..to_copy].copy_from_slice(&remaining[..to_copy]);
+        self.pos += to_copy;
+        Ok(to_copy)
+    }
+}
+
+/// A file that is mapped into memory once and serves ranges directly from the mapping
+///
+/// # Fallback
+/// If the file cannot be mapped (e.g. because it is empty or not a regular file), or if the file length or modification
+/// time has changed since it was mapped, ranges are served via positional reads instead. This check is best-effort
+/// only and does not make it sound to modify a mapped file, see [`MappedFile::new`].
+#[derive(Debug)]
+pub struct MappedFile {
+    /// The underlying file
+    file: Arc<File>,
+    /// The file mapping if the file could be mapped
+    mapping: Option<Arc<Mapping>>,
+    /// The file length at the time of mapping
+    len: u64,
+    /// The modification time at the time of mapping
+    modified: Option<SystemTime>,
+}
+impl MappedFile {
+    /// Maps the given file into memory or falls back to regular reads if the file cannot be mapped
+    ///
+    /// # Safety
+    /// If the file is mapped (see [`Self::is_mapped`]), the caller must ensure that the file is neither truncated nor
+    /// modified (by this or any other process) as long as `self` or any source opened from it is alive. Reading a mapping
+    /// of a truncated file raises `SIGBUS`, and modifications are visible through the shared byte slices, which is
+    /// undefined behavior.
+    pub unsafe fn new<T>(file: T) -> Result<Self, Error>
+    where
+        T: Into<File>,
+    {
+        // Get the file metadata
+        let file: File = file.into();
+        let metadata = file.metadata()?;
+        let (len, modified) = (representation::file_size(&file, &metadata)?, metadata.modified().ok());
+
+        // Map regular files only
+        let mapping = match (metadata.is_file(), usize::try_from(len)) {
+            (true, Ok(len)) => Mapping::new(&file, len).ok().map(Arc::new),
+            _ => None,
+        };
+        Ok(Self { file: Arc::new(file), mapping, len, modified })
+    }
+
+    /// Whether the file is served from a memory mapping or not
+    pub fn is_mapped(&self) -> bool {
+        self.mapping.is_some()
+    }
+
+    /// Returns the mapping if it exists and the file has not been modified since it was mapped
+    fn valid_mapping(&self) -> Result<Option<&Arc<Mapping>>, Error> {
+        let Some(mapping) = &self.mapping else {
+            return Ok(None);
+        };
+
+        // Ensure that the file is unchanged
+        let metadata = self.file.metadata()?;
+        match metadata.len() == self.len && metadata.modified().ok() == self.modified {
+            true => Ok(Some(mapping)),
+            false => Ok(None),
+        }
+    }
+}
+impl Representation for MappedFile {
+    fn complete_length(&self) -> Result<Option<u64>, Error> {
+        self.file.complete_length()
+    }
+
+    fn etag(&self) -> Result<Option<Data>, Error> {
+        self.file.etag()
+    }
+    fn last_modified(&self) -> Result<Option<SystemTime>, Error> {
+        self.file.last_modified()
+    }
+
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error> {
+        // Fall back to positional reads if the mapping is unavailable or outdated
+        let Some(mapping) = self.valid_mapping()? else {
+            return self.file.open_range(range);
+        };
+
+        // Validate the range
+        let start = usize::try_from(range.start).map_err(|e| err!(with: e, "Range would exceed file size"))?;
+        let end = usize::try_from(range.end).map_err(|e| err!(with: e, "Range would exceed file size"))?;
+        if start > end || end > mapping.len {
+            return Err(err!("Range would exceed file size"));
+        }
+
+        // Serve the range from the mapping
+        let reader = MappingRange { mapping: mapping.clone(), pos: start, end };
+        Ok(Source::new(reader))
+    }
+}
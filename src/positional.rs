@@ -0,0 +1,94 @@
+//! Positional reads on shared file handles
+
+use crate::representation::{self, Representation};
+use ehttpd::bytes::{Data, Source};
+use ehttpd::err;
+use ehttpd::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader, ErrorKind, Read};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// A reader over a range of a shared file that uses positional reads instead of the file cursor
+///
+/// # Rationale
+/// Since positional reads do not modify the file cursor, many readers can share the same file descriptor without racing
+/// on the cursor.
+#[derive(Debug, Clone)]
+pub struct PositionalReader {
+    /// The shared file
+    file: Arc<File>,
+    /// The current offset within the file
+    offset: u64,
+    /// The end of the range within the file
+    end: u64,
+}
+impl PositionalReader {
+    /// Creates a new positional reader over the given range of the shared file
+    pub fn new(file: Arc<File>, range: Range<u64>) -> Self {
+        Self { file, offset: range.start, end: range.end.max(range.start) }
+    }
+
+    /// The remaining bytes within the range
+    pub const fn remaining(&self) -> u64 {
+        self.end - self.offset
+    }
+}
+impl Read for PositionalReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Read the next chunk
+        let to_read = (buf.len() as u64).min(self.remaining()) as usize;
+        let read = read_at(&self.file, &mut buf[..to_read], self.offset)?;
+        if read == 0 && to_read > 0 {
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, "file has been truncated"));
+        }
+
+        // Advance the offset
+        self.offset += read as u64;
+        Ok(read)
+    }
+}
+
+/// Reads from the given file at the given offset without using the file cursor
+#[cfg(unix)]
+pub(crate) fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    use std::os::unix::fs::FileExt;
+    file.read_at(buf, offset)
+}
+/// Reads from the given file at the given offset
+///
+/// # Note
+/// On Windows, this function updates the file cursor; however since all positional readers pass an explicit offset,
+/// this does not cause any races between them.
+#[cfg(windows)]
+pub(crate) fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    use std::os::windows::fs::FileExt;
+    file.seek_read(buf, offset)
+}
+
+impl Representation for Arc<File> {
+    fn complete_length(&self) -> Result<Option<u64>, Error> {
+        self.as_ref().complete_length()
+    }
+
+    fn etag(&self) -> Result<Option<Data>, Error> {
+        self.as_ref().etag()
+    }
+    fn last_modified(&self) -> Result<Option<SystemTime>, Error> {
+        self.as_ref().last_modified()
+    }
+
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error> {
+        // Validate the range
+        let file_size = representation::file_size(self, &self.metadata()?)?;
+        if range.start > range.end || range.end > file_size {
+            return Err(err!("Range would exceed file size"));
+        }
+
+        // Buffer the reader
+        let reader = PositionalReader::new(self.clone(), range);
+        let reader = BufReader::new(reader);
+        Ok(Source::new(reader))
+    }
+}
@@ -1,5 +1,6 @@
 //! Utilities to work with range bounds and ranges
 
+use std::marker::PhantomData;
 use std::ops::{Bound, Range, RangeBounds, RangeInclusive};
 
 /// An extension trait for ranges
@@ -11,7 +12,192 @@ where
     fn from_range_bounds<B>(bounds: B, min_incl: T, max_excl: T) -> Option<Self>
     where
         B: RangeBounds<T>;
+
+    /// Splits `self` into consecutive chunks of `size` elements; the last chunk may be shorter
+    ///
+    /// # Panics
+    /// This function panics if `size` is `0`
+    fn chunks(&self, size: T) -> Chunks<T, Self>;
+    /// Splits `self` into at most `parts` consecutive chunks whose lengths differ by at most one element
+    ///
+    /// # Panics
+    /// This function panics if `parts` is `0`
+    fn split_parts(&self, parts: T) -> SplitParts<T, Self>;
+    /// Splits `self` along multiples of `alignment`; the first and the last chunk may be partial
+    ///
+    /// # Panics
+    /// This function panics if `alignment` is `0`
+    fn aligned_chunks(&self, alignment: T) -> AlignedChunks<T, Self>;
+}
+
+/// An iterator over fixed-size chunks of a range
+#[derive(Debug, Clone)]
+pub struct Chunks<T, R> {
+    /// The remaining inclusive bounds if any
+    remaining: Option<(T, T)>,
+    /// The chunk size
+    size: T,
+    /// The yielded range type
+    _range: PhantomData<R>,
+}
+
+/// An iterator over near-equal parts of a range
+#[derive(Debug, Clone)]
+pub struct SplitParts<T, R> {
+    /// The remaining inclusive bounds if any
+    remaining: Option<(T, T)>,
+    /// The length of the shorter parts minus one (so that a part may span the entire integer space), or `None` if the
+    /// shorter parts are empty
+    span: Option<T>,
+    /// The amount of remaining parts that are one element longer than the shorter parts
+    longer: T,
+    /// The yielded range type
+    _range: PhantomData<R>,
+}
+
+/// An iterator over alignment-bounded chunks of a range
+#[derive(Debug, Clone)]
+pub struct AlignedChunks<T, R> {
+    /// The remaining inclusive bounds if any
+    remaining: Option<(T, T)>,
+    /// The alignment
+    alignment: T,
+    /// The yielded range type
+    _range: PhantomData<R>,
+}
+
+/// Implements the chunk iterators for the given unsigned integer
+macro_rules! impl_chunks_uint {
+    ($uint:ty) => {
+        impl<R> Chunks<$uint, R> {
+            /// Creates a new chunk iterator over the given inclusive bounds
+            fn new(remaining: Option<($uint, $uint)>, size: $uint) -> Self {
+                assert!(size != 0, "chunk size must not be zero");
+                Self { remaining, size, _range: PhantomData }
+            }
+
+            /// Yields the next chunk as inclusive bounds
+            fn next_inclusive(&mut self) -> Option<($uint, $uint)> {
+                let (start, end_incl) = self.remaining?;
+                let chunk_end_incl = start.saturating_add(self.size - 1).min(end_incl);
+
+                // Update the remaining range
+                self.remaining = match chunk_end_incl < end_incl {
+                    true => Some((chunk_end_incl + 1, end_incl)),
+                    false => None,
+                };
+                Some((start, chunk_end_incl))
+            }
+        }
+
+        impl<R> SplitParts<$uint, R> {
+            /// Creates a new split iterator over the given inclusive bounds
+            fn new(remaining: Option<($uint, $uint)>, parts: $uint) -> Self {
+                assert!(parts != 0, "part count must not be zero");
+                let Some((start, end_incl)) = remaining else {
+                    return Self { remaining, span: None, longer: 0, _range: PhantomData };
+                };
+
+                // Compute `len / parts` and `len % parts` without computing `len`, which may overflow
+                let total_span = end_incl - start;
+                let (quotient, remainder) = (total_span / parts, total_span % parts);
+                let (span, longer) = match remainder + 1 == parts {
+                    // `len / parts` is `quotient + 1` without remainder
+                    true => (Some(quotient), 0),
+                    false => (quotient.checked_sub(1), remainder + 1),
+                };
+                Self { remaining, span, longer, _range: PhantomData }
+            }
+
+            /// Yields the next part as inclusive bounds
+            fn next_inclusive(&mut self) -> Option<($uint, $uint)> {
+                let (start, end_incl) = self.remaining?;
+
+                // Compute the length of the current part minus one
+                let span = match self.longer {
+                    0 => self.span?,
+                    _ => {
+                        // Note: Longer parts only exist if the shorter parts cannot span the entire integer space
+                        self.longer -= 1;
+                        self.span.map_or(0, |span| span + 1)
+                    }
+                };
+
+                // Update the remaining range
+                let part_end_incl = start + span;
+                self.remaining = match part_end_incl < end_incl {
+                    true => Some((part_end_incl + 1, end_incl)),
+                    false => None,
+                };
+                Some((start, part_end_incl))
+            }
+        }
+
+        impl<R> AlignedChunks<$uint, R> {
+            /// Creates a new aligned chunk iterator over the given inclusive bounds
+            fn new(remaining: Option<($uint, $uint)>, alignment: $uint) -> Self {
+                assert!(alignment != 0, "alignment must not be zero");
+                Self { remaining, alignment, _range: PhantomData }
+            }
+
+            /// Yields the next chunk as inclusive bounds
+            fn next_inclusive(&mut self) -> Option<($uint, $uint)> {
+                let (start, end_incl) = self.remaining?;
+
+                // Compute the next boundary; if it would overflow, the chunk extends to the end of the integer space
+                let base = start - (start % self.alignment);
+                let chunk_end_incl = match base.checked_add(self.alignment) {
+                    Some(boundary) => (boundary - 1).min(end_incl),
+                    None => end_incl,
+                };
+
+                // Update the remaining range
+                self.remaining = match chunk_end_incl < end_incl {
+                    true => Some((chunk_end_incl + 1, end_incl)),
+                    false => None,
+                };
+                Some((start, chunk_end_incl))
+            }
+        }
+    };
 }
+impl_chunks_uint!(u64);
+impl_chunks_uint!(usize);
+
+/// Implements `Iterator` for the chunk iterators yielding the given range type
+macro_rules! impl_chunks_iterator {
+    ($uint:ty, $range:ty, |$start:ident, $end_incl:ident| $make:expr) => {
+        impl Iterator for Chunks<$uint, $range> {
+            type Item = $range;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let ($start, $end_incl) = self.next_inclusive()?;
+                Some($make)
+            }
+        }
+        impl Iterator for SplitParts<$uint, $range> {
+            type Item = $range;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let ($start, $end_incl) = self.next_inclusive()?;
+                Some($make)
+            }
+        }
+        impl Iterator for AlignedChunks<$uint, $range> {
+            type Item = $range;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let ($start, $end_incl) = self.next_inclusive()?;
+                Some($make)
+            }
+        }
+    };
+}
+// Note: The exclusive end cannot overflow since the chunk is always within the originating exclusive range
+impl_chunks_iterator!(u64, Range<u64>, |start, end_incl| start..end_incl + 1);
+impl_chunks_iterator!(usize, Range<usize>, |start, end_incl| start..end_incl + 1);
+impl_chunks_iterator!(u64, RangeInclusive<u64>, |start, end_incl| start..=end_incl);
+impl_chunks_iterator!(usize, RangeInclusive<usize>, |start, end_incl| start..=end_incl);
 
 /// Implements `RangeExt` for `RangeInclusive` with the given unsized integer
 macro_rules! impl_rangeext_range_uint {
@@ -42,6 +228,19 @@ macro_rules! impl_rangeext_range_uint {
                 }
                 Some(start..end)
             }
+
+            fn chunks(&self, size: $uint) -> Chunks<$uint, Self> {
+                let bounds = (self.start < self.end).then(|| (self.start, self.end - 1));
+                Chunks::<$uint, Self>::new(bounds, size)
+            }
+            fn split_parts(&self, parts: $uint) -> SplitParts<$uint, Self> {
+                let bounds = (self.start < self.end).then(|| (self.start, self.end - 1));
+                SplitParts::<$uint, Self>::new(bounds, parts)
+            }
+            fn aligned_chunks(&self, alignment: $uint) -> AlignedChunks<$uint, Self> {
+                let bounds = (self.start < self.end).then(|| (self.start, self.end - 1));
+                AlignedChunks::<$uint, Self>::new(bounds, alignment)
+            }
         }
     };
 }
@@ -77,6 +276,19 @@ macro_rules! impl_rangeext_rangeinclusive_uint {
                 }
                 Some(start..=end_incl)
             }
+
+            fn chunks(&self, size: $uint) -> Chunks<$uint, Self> {
+                let bounds = (!self.is_empty()).then(|| (*self.start(), *self.end()));
+                Chunks::<$uint, Self>::new(bounds, size)
+            }
+            fn split_parts(&self, parts: $uint) -> SplitParts<$uint, Self> {
+                let bounds = (!self.is_empty()).then(|| (*self.start(), *self.end()));
+                SplitParts::<$uint, Self>::new(bounds, parts)
+            }
+            fn aligned_chunks(&self, alignment: $uint) -> AlignedChunks<$uint, Self> {
+                let bounds = (!self.is_empty()).then(|| (*self.start(), *self.end()));
+                AlignedChunks::<$uint, Self>::new(bounds, alignment)
+            }
         }
     };
 }
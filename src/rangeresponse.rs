@@ -1,13 +1,66 @@
 //! An extension trait for HTTP requests to work with range requests
 
+use crate::advice::{AdvicePolicy, DontNeedReader};
+use crate::anyrange::AnyInclusiveRange;
+use crate::bufpool::{self, BufferPool, PooledReader};
+use crate::concat::{Concat, Segment};
+use crate::cursor::DataCursor;
+use crate::forward::ForwardSource;
+use crate::guarded::{FileSnapshot, GuardedReader};
+#[cfg(all(target_os = "linux", feature = "seekhole"))]
+use crate::holes::HoleReader;
+use crate::httpdate;
 use crate::rangeext::RangeExt;
+use crate::readplan::ReadPlanner;
+use crate::representation::{self, Representation};
+use crate::spool::Fnv1a;
+use crate::tail::{TailOptions, TailReader};
 use ehttpd::bytes::{Data, Source};
 use ehttpd::err;
 use ehttpd::error::Error;
 use ehttpd::http::Response;
+use std::fmt::Debug;
 use std::fs::File;
-use std::io::{BufReader, Read, Seek, SeekFrom};
-use std::ops::{Range, RangeBounds, RangeInclusive};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::ops::{Bound, Range, RangeBounds, RangeInclusive};
+use std::process;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Options for ranged file bodies
+#[derive(Debug, Clone, Default)]
+pub struct FileRangeOptions {
+    /// The page-cache hint policy if any
+    pub advice: Option<AdvicePolicy>,
+    /// The buffer size if the body does not use a buffer pool (defaults to [`bufpool::DEFAULT_BUFFER_SIZE`])
+    pub buffer_size: Option<usize>,
+    /// The buffer pool to borrow the body buffer from if any
+    pub pool: Option<Arc<BufferPool>>,
+    /// The amount of bytes after which the file is re-validated while it is being served; the body fails with a
+    /// [`FileChangedError`](crate::guarded::FileChangedError) if the file has changed (`None` to disable)
+    pub guard_interval: Option<u64>,
+    /// Whether the complete length is reported as unknown (e.g. `bytes 7-9/*` for files that are still being written)
+    pub unknown_length: bool,
+    /// Whether holes in sparse files are synthesized as zeros instead of being read from disk (requires the `seekhole`
+    /// feature on Linux and is ignored otherwise, or if `guard_interval` is set)
+    pub skip_holes: bool,
+}
+impl FileRangeOptions {
+    /// Buffers the given reader according to the options
+    fn buffer<T>(&self, reader: T) -> Source
+    where
+        T: Read + Debug + Send + Sync + 'static,
+    {
+        match &self.pool {
+            Some(pool) => Source::new(PooledReader::new(reader, pool)),
+            None => {
+                let buffer_size = self.buffer_size.unwrap_or(bufpool::DEFAULT_BUFFER_SIZE);
+                Source::new(BufReader::with_capacity(buffer_size, reader))
+            }
+        }
+    }
+}
 
 /// An extension trait for HTTP responses to work with range requests
 pub trait RangeResponse
@@ -24,9 +77,26 @@ where
 
     /// Sets the `Content-Range` header
     fn set_content_range<T>(&mut self, range: T, total: u64) -> Result<(), Error>
+    where
+        T: RangeBounds<u64>;
+    /// Sets the `Content-Range` header for a representation with an unknown complete length (e.g. `bytes 7-9/*`)
+    ///
+    /// # Note
+    /// Since the complete length is unknown, the range must have an explicit end
+    fn set_content_range_unknown<T>(&mut self, range: T) -> Result<(), Error>
     where
         T: RangeBounds<u64>;
 
+    /// Sets the body for a `Partial Range` response from the given representation
+    ///
+    /// # Note
+    /// This function also sets the `Content-Length` and the `Content-Range` headers, as well as the `ETag`,
+    /// `Last-Modified` and `Content-Type` headers if the representation provides them. Furthermore, it raises an error
+    /// if `self.status` is not `206`
+    fn set_body_range<T, R>(&mut self, representation: &T, range: R) -> Result<(), Error>
+    where
+        T: Representation + ?Sized,
+        R: RangeBounds<u64>;
     /// Sets the body for a `Partial Range` response
     ///
     /// # Note
@@ -36,6 +106,16 @@ where
     where
         T: Into<Data>,
         R: RangeBounds<usize>;
+    /// Sets the body for a `Partial Range` response from a list of data chunks that are treated as one contiguous body
+    ///
+    /// # Note
+    /// Only the slices of the chunks that overlap with the range are streamed; the chunks are neither copied nor
+    /// concatenated. This function also sets the `Content-Length` and the `Content-Range` headers. Furthermore, it
+    /// raises an error if `self.status` is not `206`
+    fn set_body_data_chunks_range<T, R>(&mut self, chunks: T, range: R) -> Result<(), Error>
+    where
+        T: IntoIterator<Item = Data>,
+        R: RangeBounds<u64>;
     /// Sets the body for a `Partial Range` response
     ///
     /// # Note
@@ -45,6 +125,68 @@ where
     where
         T: Into<File>,
         R: RangeBounds<u64>;
+    /// Sets the body for a `Partial Range` response with the given options
+    ///
+    /// # Note
+    /// This function also sets the `Content-Length` and the `Content-Range` headers. Furthermore, it raises an error if
+    /// `self.status` is not `206`
+    fn set_body_file_range_with<T, R>(&mut self, file: T, range: R, options: &FileRangeOptions) -> Result<(), Error>
+    where
+        T: Into<File>,
+        R: RangeBounds<u64>;
+    /// Sets a `multipart/byteranges` body for a `Partial Range` response with multiple ranges of a file
+    ///
+    /// # Note
+    /// The reads are planned by `planner`, so that nearby ranges are coalesced into one read while the parts are still
+    /// emitted in the given order. If `self` has a `Content-Type` header, it is moved into the parts. This function also
+    /// sets the `Content-Length` and the `Content-Type` headers. Since a single range must not be sent as multipart body,
+    /// a single range is served like [`Self::set_body_file_range_with`] with the default options instead. Furthermore, it
+    /// raises an error if `self.status` is not `206`
+    fn set_body_file_ranges<T, R>(&mut self, file: T, ranges: &[R], planner: &ReadPlanner) -> Result<(), Error>
+    where
+        T: Into<File>,
+        R: RangeBounds<u64>;
+    /// Sets the body for a `Partial Range` response from a forward-only stream by discarding all bytes before the range
+    ///
+    /// # Note
+    /// This function also sets the `Content-Length` and the `Content-Range` headers; if the stream length is unknown, the
+    /// complete length is reported as unknown. Furthermore, it raises an error if `self.status` is not `206`
+    fn set_body_forward_range<T>(
+        &mut self,
+        source: ForwardSource<T>,
+        range: AnyInclusiveRange<u64>,
+    ) -> Result<(), Error>
+    where
+        T: Read + Debug + Send + Sync + 'static;
+    /// Sets a body that serves the file from the beginning and keeps following the growing file until a stop condition
+    /// is met
+    ///
+    /// # Note
+    /// Since a `200` response carries the complete representation, the body always starts at the beginning of the file.
+    /// Since the end of the body is unknown, it cannot be described by `Content-Range` or `Content-Length`. This
+    /// function thus removes the `Content-Length` header and sets `Connection: Close` to delimit the body. Furthermore,
+    /// it raises an error if `self.status` is not `200`
+    fn set_body_file_tail<T>(&mut self, file: T, options: TailOptions) -> Result<(), Error>
+    where
+        T: Into<File>;
+    /// Sets the body for a `Partial Range` response from an arbitrary seekable reader
+    ///
+    /// # Note
+    /// This function determines the total length by seeking to the end of `reader`. It also sets the `Content-Length`
+    /// and the `Content-Range` headers. Furthermore, it raises an error if `self.status` is not `206`
+    fn set_body_reader_range<T, R>(&mut self, reader: T, range: R) -> Result<(), Error>
+    where
+        T: Read + Seek + Debug + Send + Sync + 'static,
+        R: RangeBounds<u64>;
+    /// Sets the body for a `Partial Range` response from an arbitrary seekable reader with the given total length
+    ///
+    /// # Note
+    /// This function also sets the `Content-Length` and the `Content-Range` headers. Furthermore, it raises an error if
+    /// `self.status` is not `206`
+    fn set_body_reader_range_len<T, R>(&mut self, reader: T, total: u64, range: R) -> Result<(), Error>
+    where
+        T: Read + Seek + Debug + Send + Sync + 'static,
+        R: RangeBounds<u64>;
 }
 impl RangeResponse for Response {
     fn new_206_partial_content() -> Self {
@@ -71,7 +213,56 @@ impl RangeResponse for Response {
         self.set_field("Content-Range", range_string);
         Ok(())
     }
+    fn set_content_range_unknown<T>(&mut self, range: T) -> Result<(), Error>
+    where
+        T: RangeBounds<u64>,
+    {
+        // Ensure that the range has an end
+        if let Bound::Unbounded = range.end_bound() {
+            return Err(err!("Range end is unknown"));
+        }
+
+        // Compute the bounds
+        let range = RangeInclusive::from_range_bounds(range, 0, u64::MAX).ok_or_else(|| err!("Range is invalid"))?;
+        let range_string = format!("bytes {}-{}/*", range.start(), range.end());
 
+        // Set the range
+        self.set_field("Content-Range", range_string);
+        Ok(())
+    }
+
+    fn set_body_range<T, R>(&mut self, representation: &T, range: R) -> Result<(), Error>
+    where
+        T: Representation + ?Sized,
+        R: RangeBounds<u64>,
+    {
+        // Ensure that we are a 206
+        if !self.status.eq(b"206") {
+            return Err(err!("Response is not a 206 response"));
+        }
+
+        // Validate the range
+        // Note: If the complete length is unknown, the range must have an explicit end
+        let total = representation.complete_length()?;
+        if let (None, Bound::Unbounded) = (total, range.end_bound()) {
+            return Err(err!("Complete length of representation is unknown"));
+        }
+        let Range { start, end } = Range::from_range_bounds(range, 0, total.unwrap_or(u64::MAX))
+            .ok_or_else(|| err!("Range would exceed representation size"))?;
+        let body = representation.open_range(start..end)?;
+
+        // Set content-range and content-length header
+        match total {
+            Some(total) => self.set_content_range(start..end, total)?,
+            None => self.set_content_range_unknown(start..end)?,
+        }
+        self.set_content_length(end.saturating_sub(start));
+
+        // Set the metadata headers and the body
+        set_representation_fields(self, representation)?;
+        self.body = body;
+        Ok(())
+    }
     fn set_body_data_range<T, R>(&mut self, data: T, range: R) -> Result<(), Error>
     where
         T: Into<Data>,
@@ -82,18 +273,40 @@ impl RangeResponse for Response {
             return Err(err!("Response is not a 206 response"));
         }
 
-        // Prepare data and range
+        // Validate the range
         let data: Data = data.into();
-        let Range { start, end } =
-            Range::from_range_bounds(range, 0, data.len()).ok_or_else(|| err!("Range would exceed data size"))?;
-        let subdata = data.subcopy(start..end).expect("range would exceed data size");
+        let total = data.len() as u64;
+        let range = (range.start_bound().map(|start| *start as u64), range.end_bound().map(|end| *end as u64));
+        let range = Range::from_range_bounds(range, 0, total).ok_or_else(|| err!("Range would exceed data size"))?;
+
+        // Narrow the data to the requested range without copying
+        let body = seek_body(DataCursor::new(data), &range, |mut cursor, len| {
+            let view = cursor.read_view(len as usize)?;
+            Ok(Source::from(view))
+        })?;
 
-        // Set content-range header and body data
-        self.set_content_range(start as u64..end as u64, data.len() as u64)?;
-        self.set_body_data(subdata);
+        // Set content-range and content-length header
+        self.set_content_range(range.clone(), total)?;
+        self.set_content_length(range.end - range.start);
+        self.body = body;
         Ok(())
     }
+    fn set_body_data_chunks_range<T, R>(&mut self, chunks: T, range: R) -> Result<(), Error>
+    where
+        T: IntoIterator<Item = Data>,
+        R: RangeBounds<u64>,
+    {
+        let chunks = Concat::new(chunks.into_iter().map(Segment::Data))?;
+        self.set_body_range(&chunks, range)
+    }
     fn set_body_file_range<T, R>(&mut self, file: T, range: R) -> Result<(), Error>
+    where
+        T: Into<File>,
+        R: RangeBounds<u64>,
+    {
+        self.set_body_file_range_with(file, range, &FileRangeOptions::default())
+    }
+    fn set_body_file_range_with<T, R>(&mut self, file: T, range: R, options: &FileRangeOptions) -> Result<(), Error>
     where
         T: Into<File>,
         R: RangeBounds<u64>,
@@ -103,24 +316,250 @@ impl RangeResponse for Response {
             return Err(err!("Response is not a 206 response"));
         }
 
-        // Open the file and get the file size
-        let mut file: File = file.into();
-        let file_size = file.metadata()?.len();
-        let Range { start, end } =
+        // Get the file size and validate the range
+        let file: File = file.into();
+        let metadata = file.metadata()?;
+        let file_size = representation::file_size(&file, &metadata)?;
+        let range =
             Range::from_range_bounds(range, 0, file_size).ok_or_else(|| err!("Range would exceed file size"))?;
 
-        // Get the length and virtually truncate the file
-        let len = end.saturating_sub(start);
-        file.seek(SeekFrom::Start(start))?;
-        let file = file.take(end.saturating_sub(start));
+        // Apply the page-cache hints
+        // Note: The hints are best-effort, so errors are ignored
+        if let Some(advice) = &options.advice {
+            let _ = advice.apply(&file, &range);
+        }
+
+        // Create the reader
+        let len = range.end.saturating_sub(range.start);
+        let should_drop = options.advice.is_some_and(|advice| advice.should_drop(&range));
+        let reader = match options.guard_interval {
+            Some(interval) => {
+                // Guard the file against modifications
+                let snapshot = FileSnapshot::new(&metadata);
+                let reader = GuardedReader::new(file, snapshot, range.clone(), interval)?;
+                match should_drop {
+                    true => options.buffer(DontNeedReader { reader, range: range.clone() }),
+                    false => options.buffer(reader),
+                }
+            }
+            #[cfg(all(target_os = "linux", feature = "seekhole"))]
+            None if options.skip_holes => {
+                // Synthesize zeros for holes instead of reading them
+                let reader = HoleReader::new(file, range.clone());
+                match should_drop {
+                    true => options.buffer(DontNeedReader { reader, range: range.clone() }),
+                    false => options.buffer(reader),
+                }
+            }
+            None => seek_body(file, &range, |file, len| {
+                // Virtually truncate the file
+                let reader = file.take(len);
+                match should_drop {
+                    true => Ok(options.buffer(DontNeedReader { reader, range: range.clone() })),
+                    false => Ok(options.buffer(reader)),
+                }
+            })?,
+        };
 
         // Set content-range and content-length header
-        self.set_content_range(start..end, file_size)?;
+        match options.unknown_length {
+            true => self.set_content_range_unknown(range)?,
+            false => self.set_content_range(range, file_size)?,
+        }
+        self.set_content_length(len);
+        self.body = reader;
+        Ok(())
+    }
+    fn set_body_file_ranges<T, R>(&mut self, file: T, ranges: &[R], planner: &ReadPlanner) -> Result<(), Error>
+    where
+        T: Into<File>,
+        R: RangeBounds<u64>,
+    {
+        // Ensure that we are a 206
+        if !self.status.eq(b"206") {
+            return Err(err!("Response is not a 206 response"));
+        }
+
+        // Get the file size and validate the ranges
+        let file: File = file.into();
+        let file_size = representation::file_size(&file, &file.metadata()?)?;
+        let mut resolved = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            let bounds = (range.start_bound(), range.end_bound());
+            let range = Range::from_range_bounds(bounds, 0, file_size).filter(|range| !range.is_empty());
+            resolved.push(range.ok_or_else(|| err!("Range would exceed file size or is empty"))?);
+        }
+        if resolved.is_empty() {
+            return Err(err!("No ranges given"));
+        }
+
+        // Serve a single range as a single part (see RFC 9110, section 14.6)
+        if let [range] = resolved.as_slice() {
+            return self.set_body_file_range_with(file, range.clone(), &FileRangeOptions::default());
+        }
+
+        // Take the content type of the parts
+        let content_type = (self.fields.iter())
+            .position(|(key, _)| key.eq_ignore_ascii_case(b"Content-Type"))
+            .map(|index| self.fields.remove(index).1);
+
+        // Create the part headers and the closing delimiter
+        let boundary = multipart_boundary();
+        let mut framing = Vec::with_capacity(resolved.len());
+        for (index, range) in resolved.iter().enumerate() {
+            let mut header = match index {
+                0 => format!("--{boundary}\r\n").into_bytes(),
+                _ => format!("\r\n--{boundary}\r\n").into_bytes(),
+            };
+            if let Some(content_type) = &content_type {
+                header.extend_from_slice(b"Content-Type: ");
+                header.extend_from_slice(content_type);
+                header.extend_from_slice(b"\r\n");
+            }
+            let Range { start, end } = range;
+            header
+                .extend_from_slice(format!("Content-Range: bytes {start}-{}/{file_size}\r\n\r\n", end - 1).as_bytes());
+            framing.push(Data::from(header));
+        }
+        let suffix = Data::from(format!("\r\n--{boundary}--\r\n"));
+
+        // Compute the content length
+        let framing_len: u64 = framing.iter().map(|framing| framing.len() as u64).sum();
+        let parts_len: u64 = resolved.iter().map(|range| range.end - range.start).sum();
+        let len = framing_len + parts_len + suffix.len() as u64;
+
+        // Plan the reads and set the body
+        let reader = planner.plan(&resolved).reader_framed(Arc::new(file), framing, suffix);
+        self.set_content_type(format!("multipart/byteranges; boundary={boundary}"));
         self.set_content_length(len);
+        self.body = Source::new(reader);
+        Ok(())
+    }
+    fn set_body_forward_range<T>(
+        &mut self,
+        source: ForwardSource<T>,
+        range: AnyInclusiveRange<u64>,
+    ) -> Result<(), Error>
+    where
+        T: Read + Debug + Send + Sync + 'static,
+    {
+        // Ensure that we are a 206
+        if !self.status.eq(b"206") {
+            return Err(err!("Response is not a 206 response"));
+        }
+
+        // Resolve the range
+        let range = source.resolve(range)?;
+        match source.stream_len() {
+            Some(total) => self.set_content_range(range.clone(), total)?,
+            None => self.set_content_range_unknown(range.clone())?,
+        }
 
-        // Buffer the file and set the raw body
-        let file = BufReader::new(file);
-        self.body = Source::new(file);
+        // Set the content length and the body
+        self.set_content_length(range.end.saturating_sub(range.start));
+        self.body = Source::new(source.into_range(range));
         Ok(())
     }
+    fn set_body_file_tail<T>(&mut self, file: T, options: TailOptions) -> Result<(), Error>
+    where
+        T: Into<File>,
+    {
+        // Ensure that we are a 200
+        if !self.status.eq(b"200") {
+            return Err(err!("Response is not a 200 response"));
+        }
+
+        // Delimit the body by closing the connection
+        self.fields.retain(|(key, _)| !key.eq_ignore_ascii_case(b"Content-Length"));
+        self.set_connection_close();
+
+        // Set the body
+        let reader = TailReader::new(file.into(), 0, options)?;
+        self.body = Source::new(BufReader::new(reader));
+        Ok(())
+    }
+    fn set_body_reader_range<T, R>(&mut self, mut reader: T, range: R) -> Result<(), Error>
+    where
+        T: Read + Seek + Debug + Send + Sync + 'static,
+        R: RangeBounds<u64>,
+    {
+        // Ensure that we are a 206
+        if !self.status.eq(b"206") {
+            return Err(err!("Response is not a 206 response"));
+        }
+
+        // Get the total length by seeking to the end
+        let total = reader.seek(SeekFrom::End(0))?;
+        self.set_body_reader_range_len(reader, total, range)
+    }
+    fn set_body_reader_range_len<T, R>(&mut self, reader: T, total: u64, range: R) -> Result<(), Error>
+    where
+        T: Read + Seek + Debug + Send + Sync + 'static,
+        R: RangeBounds<u64>,
+    {
+        // Ensure that we are a 206
+        if !self.status.eq(b"206") {
+            return Err(err!("Response is not a 206 response"));
+        }
+
+        // Validate the range
+        let Range { start, end } =
+            Range::from_range_bounds(range, 0, total).ok_or_else(|| err!("Range would exceed reader size"))?;
+
+        // Virtually truncate and buffer the reader
+        let body = seek_body(reader, &(start..end), |reader, len| {
+            let reader = BufReader::new(reader.take(len));
+            Ok(Source::new(reader))
+        })?;
+
+        // Set content-range and content-length header
+        self.set_content_range(start..end, total)?;
+        self.set_content_length(end.saturating_sub(start));
+        self.body = body;
+        Ok(())
+    }
+}
+
+/// Seeks the reader to the start of the range and creates the body for the range length via `into_body`
+///
+/// # Note
+/// This is the shared implementation of the data, file and reader paths
+fn seek_body<T, F>(mut reader: T, range: &Range<u64>, into_body: F) -> Result<Source, Error>
+where
+    T: Seek,
+    F: FnOnce(T, u64) -> Result<Source, Error>,
+{
+    reader.seek(SeekFrom::Start(range.start))?;
+    into_body(reader, range.end.saturating_sub(range.start))
+}
+
+/// Creates a boundary for multipart bodies that is unique for this process
+fn multipart_boundary() -> String {
+    /// A counter to create unique boundaries
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    // Hash the process ID, the time and the counter
+    let mut hasher = Fnv1a::default();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let seed = format!("{}.{}.{counter}", process::id(), now.as_nanos());
+    hasher.write_all(seed.as_bytes()).expect("failed to hash boundary seed");
+    format!("ehttpd-range-{:016x}{counter:x}", hasher.0)
+}
+
+/// Sets the `ETag`, `Last-Modified` and `Content-Type` headers if the representation provides them
+pub(crate) fn set_representation_fields<T>(response: &mut Response, representation: &T) -> Result<(), Error>
+where
+    T: Representation + ?Sized,
+{
+    if let Some(etag) = representation.etag()? {
+        response.set_field("ETag", etag);
+    }
+    if let Some(last_modified) = representation.last_modified()? {
+        response.set_field("Last-Modified", httpdate::format(last_modified));
+    }
+    if let Some(content_type) = representation.content_type() {
+        response.set_content_type(content_type);
+    }
+    Ok(())
 }
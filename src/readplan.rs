@@ -0,0 +1,246 @@
+//! A read planner that coalesces seeks and reads for multi-range file responses
+
+use crate::cursor::DataCursor;
+use crate::positional;
+use crate::rangeext::RangeExt;
+use ehttpd::bytes::Data;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, ErrorKind, Read};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// A read planner configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadPlanner {
+    /// The maximum gap between two ranges that is read and discarded to merge both ranges into one read
+    pub max_gap: u64,
+    /// The maximum size of a single read; larger ranges are split into multiple reads
+    pub max_read: u64,
+}
+impl ReadPlanner {
+    /// Plans the reads for the given ranges in the order the client has requested them
+    ///
+    /// # Note
+    /// Empty ranges do not need any reads and are skipped.
+    pub fn plan(&self, ranges: &[Range<u64>]) -> ReadPlan {
+        // Sort the indices of the non-empty ranges by file order
+        let mut order: Vec<usize> = (0..ranges.len()).filter(|index| !ranges[*index].is_empty()).collect();
+        order.sort_by_key(|index| (ranges[*index].start, ranges[*index].end));
+
+        // Merge ranges that overlap or are separated by small gaps, and split ranges that are too large
+        let max_read = self.max_read.max(1);
+        let (mut reads, mut part_reads) = (Vec::<PlannedRead>::new(), vec![Vec::new(); ranges.len()]);
+        for index in order {
+            for chunk in ranges[index].chunks(max_read) {
+                // Check if the chunk can be merged into the previous read
+                // Note: A chunk may start before the previous read if the previous read is the tail of a split range
+                let merged = reads.last().map(|last| last.range.start.min(chunk.start)..last.range.end.max(chunk.end));
+                let gap = reads.last().map(|last| chunk.start.saturating_sub(last.range.end));
+                match (reads.last_mut(), merged, gap) {
+                    (Some(last), Some(merged), Some(gap))
+                        if gap <= self.max_gap && merged.end - merged.start <= max_read =>
+                    {
+                        last.range = merged;
+                        last.parts.push(index);
+                    }
+                    _ => reads.push(PlannedRead { range: chunk.clone(), parts: vec![index] }),
+                }
+
+                // Map the chunk onto its read
+                part_reads[index].push((reads.len() - 1, chunk));
+            }
+        }
+        ReadPlan { requested: ranges.to_vec(), reads, part_reads }
+    }
+}
+impl Default for ReadPlanner {
+    fn default() -> Self {
+        Self { max_gap: 16 * 1024, max_read: 1024 * 1024 }
+    }
+}
+
+/// A single planned read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedRead {
+    /// The byte range to read
+    pub range: Range<u64>,
+    /// The indices of the requested ranges that are served by this read
+    pub parts: Vec<usize>,
+}
+
+/// Estimated counters that compare a read plan with reading each requested range separately
+///
+/// # Note
+/// The read counts are lower bounds that assume that each positional read fills its buffer completely, which is usually
+/// the case for regular files; see [`PlanReader::read_calls`] for the amount of reads that have actually been performed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlanStats {
+    /// The amount of requested ranges
+    pub requested: usize,
+    /// The amount of planned reads
+    pub reads: usize,
+    /// The estimated amount of positional reads if each range is read separately in chunks of at most `max_read` bytes
+    pub naive_reads: usize,
+    /// The estimated amount of positional reads of the plan
+    pub planned_reads: usize,
+}
+impl PlanStats {
+    /// The estimated amount of positional reads saved by the plan
+    pub const fn saved_reads(&self) -> usize {
+        self.naive_reads.saturating_sub(self.planned_reads)
+    }
+}
+
+/// A read plan for a set of requested ranges
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadPlan {
+    /// The requested ranges in client order
+    requested: Vec<Range<u64>>,
+    /// The planned reads in file order
+    reads: Vec<PlannedRead>,
+    /// The chunks of each requested range together with the index of the read that serves them
+    part_reads: Vec<Vec<(usize, Range<u64>)>>,
+}
+impl ReadPlan {
+    /// The planned reads in file order
+    pub fn reads(&self) -> &[PlannedRead] {
+        &self.reads
+    }
+
+    /// Estimates the plan statistics
+    ///
+    /// # Note
+    /// A naive implementation reads each chunk of each requested range separately. The plan emits the parts in client
+    /// order and only retains the most recent read, so a read is only repeated if the client order returns to it later.
+    pub fn stats(&self) -> PlanStats {
+        let (mut planned_reads, mut previous) = (0, None);
+        for (read_index, _) in self.part_reads.iter().flatten() {
+            // Count the read unless it is served from the retained buffer
+            if previous != Some(*read_index) {
+                (planned_reads, previous) = (planned_reads + 1, Some(*read_index));
+            }
+        }
+        PlanStats {
+            requested: self.requested.len(),
+            reads: self.reads.len(),
+            naive_reads: self.part_reads.iter().map(Vec::len).sum(),
+            planned_reads,
+        }
+    }
+
+    /// Creates a reader that streams the requested parts from the file in client order via positional reads
+    ///
+    /// # Note
+    /// Only the buffer of the most recent read is retained, so the memory usage is bounded by
+    /// [`ReadPlanner::max_read`].
+    pub fn reader(self, file: Arc<File>) -> PlanReader {
+        let framing = vec![Data::new_empty(); self.requested.len()];
+        self.reader_framed(file, framing, Data::new_empty())
+    }
+    /// Creates a reader that streams the requested parts in client order, where each part is preceded by its framing
+    /// and the last part is followed by `suffix`
+    pub(crate) fn reader_framed(self, file: Arc<File>, framing: Vec<Data>, suffix: Data) -> PlanReader {
+        // Schedule the framing and the reads
+        let mut pending = VecDeque::new();
+        for (part, framing) in framing.into_iter().enumerate() {
+            pending.push_back(PlanItem::Data(framing));
+            for (read_index, chunk) in &self.part_reads[part] {
+                pending.push_back(PlanItem::Read { read_index: *read_index, chunk: chunk.clone() });
+            }
+        }
+        pending.push_back(PlanItem::Data(suffix));
+        PlanReader { plan: self, file, pending, current: None, retained: None, read_calls: 0 }
+    }
+}
+
+/// A scheduled item of a plan reader
+#[derive(Debug)]
+enum PlanItem {
+    /// Raw data
+    Data(Data),
+    /// A chunk of a requested range that is served by a planned read
+    Read {
+        /// The index of the planned read
+        read_index: usize,
+        /// The chunk within the file
+        chunk: Range<u64>,
+    },
+}
+
+/// A reader that executes a read plan and streams the requested parts in client order
+#[derive(Debug)]
+pub struct PlanReader {
+    /// The read plan
+    plan: ReadPlan,
+    /// The shared file
+    file: Arc<File>,
+    /// The pending items
+    pending: VecDeque<PlanItem>,
+    /// The data that is currently being streamed
+    current: Option<DataCursor>,
+    /// The buffer of the most recent read
+    retained: Option<(usize, Data)>,
+    /// The amount of positional reads performed so far
+    read_calls: usize,
+}
+impl PlanReader {
+    /// The amount of positional read syscalls that have been performed so far
+    pub const fn read_calls(&self) -> usize {
+        self.read_calls
+    }
+
+    /// Gets the buffer of the given planned read, reading it if it is not retained
+    fn read_buffer(&mut self, read_index: usize) -> io::Result<Data> {
+        if let Some((retained_index, buffer)) = &self.retained
+            && *retained_index == read_index
+        {
+            return Ok(buffer.clone());
+        }
+
+        // Perform the read
+        let range = &self.plan.reads[read_index].range;
+        let mut buf = vec![0; (range.end - range.start) as usize];
+        let (mut filled, mut offset) = (0, range.start);
+        while filled < buf.len() {
+            let read = positional::read_at(&self.file, &mut buf[filled..], offset)?;
+            self.read_calls += 1;
+            if read == 0 {
+                return Err(io::Error::new(ErrorKind::UnexpectedEof, "file has been truncated"));
+            }
+            (filled, offset) = (filled + read, offset + read as u64);
+        }
+
+        // Retain the buffer
+        let buffer = Data::from(buf);
+        self.retained = Some((read_index, buffer.clone()));
+        Ok(buffer)
+    }
+}
+impl Read for PlanReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            // Stream the current data
+            if let Some(current) = &mut self.current {
+                match current.read(buf)? {
+                    0 => self.current = None,
+                    read => return Ok(read),
+                }
+            }
+
+            // Get the next item
+            let data = match self.pending.pop_front() {
+                None => return Ok(0),
+                Some(PlanItem::Data(data)) => data,
+                Some(PlanItem::Read { read_index, chunk }) => {
+                    // Slice the chunk out of the read without copying
+                    let buffer = self.read_buffer(read_index)?;
+                    let read = &self.plan.reads[read_index].range;
+                    let (start, end) = ((chunk.start - read.start) as usize, (chunk.end - read.start) as usize);
+                    buffer.subcopy(start..end).expect("chunk would exceed planned read")
+                }
+            };
+            self.current = Some(DataCursor::new(data));
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! A trait abstracting servable representations of a resource
+
+use ehttpd::bytes::{Data, Source};
+use ehttpd::err;
+use ehttpd::error::Error;
+use std::fs::{File, Metadata};
+use std::io::{Seek, SeekFrom};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A servable representation of a resource
+pub trait Representation {
+    /// The complete length of the representation if known
+    fn complete_length(&self) -> Result<Option<u64>, Error>;
+
+    /// The entity tag of the representation including the quotes (e.g. `"v1"` or `W/"v1"`) if any
+    fn etag(&self) -> Result<Option<Data>, Error> {
+        Ok(None)
+    }
+    /// The last modification time of the representation if known
+    fn last_modified(&self) -> Result<Option<SystemTime>, Error> {
+        Ok(None)
+    }
+    /// The content type of the representation if known
+    fn content_type(&self) -> Option<Data> {
+        None
+    }
+
+    /// Opens a reader for the given byte range
+    ///
+    /// # Note
+    /// The range must be within the complete length; implementations should raise an error otherwise
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error>;
+}
+impl<T> Representation for &T
+where
+    T: Representation + ?Sized,
+{
+    fn complete_length(&self) -> Result<Option<u64>, Error> {
+        (**self).complete_length()
+    }
+
+    fn etag(&self) -> Result<Option<Data>, Error> {
+        (**self).etag()
+    }
+    fn last_modified(&self) -> Result<Option<SystemTime>, Error> {
+        (**self).last_modified()
+    }
+    fn content_type(&self) -> Option<Data> {
+        (**self).content_type()
+    }
+
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error> {
+        (**self).open_range(range)
+    }
+}
+impl Representation for File {
+    fn complete_length(&self) -> Result<Option<u64>, Error> {
+        let metadata = self.metadata()?;
+        let file_size = file_size(self, &metadata)?;
+        Ok(Some(file_size))
+    }
+
+    fn etag(&self) -> Result<Option<Data>, Error> {
+        let metadata = self.metadata()?;
+        Ok(file_etag(&metadata))
+    }
+    fn last_modified(&self) -> Result<Option<SystemTime>, Error> {
+        let metadata = self.metadata()?;
+        Ok(metadata.modified().ok())
+    }
+
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error> {
+        // Duplicate the handle and read positionally
+        // Note: The duplicated handle shares the cursor with `self`, so we must not use the cursor at all
+        let file = Arc::new(self.try_clone()?);
+        file.open_range(range)
+    }
+}
+impl Representation for Data {
+    fn complete_length(&self) -> Result<Option<u64>, Error> {
+        Ok(Some(self.len() as u64))
+    }
+
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error> {
+        // Narrow the view without copying
+        let start = usize::try_from(range.start).map_err(|e| err!(with: e, "Range would exceed data size"))?;
+        let end = usize::try_from(range.end).map_err(|e| err!(with: e, "Range would exceed data size"))?;
+        if start > end {
+            return Err(err!("Range is invalid"));
+        }
+        let view = self.subcopy(start..end).ok_or_else(|| err!("Range would exceed data size"))?;
+        Ok(Source::from(view))
+    }
+}
+impl Representation for Arc<[u8]> {
+    fn complete_length(&self) -> Result<Option<u64>, Error> {
+        Ok(Some(self.len() as u64))
+    }
+
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error> {
+        let data = Data::Heap { data: self.clone(), range: 0..self.len() };
+        data.open_range(range)
+    }
+}
+impl Representation for Vec<u8> {
+    fn complete_length(&self) -> Result<Option<u64>, Error> {
+        Ok(Some(self.len() as u64))
+    }
+
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error> {
+        // Copy the requested range since we cannot share the vector
+        let start = usize::try_from(range.start).map_err(|e| err!(with: e, "Range would exceed data size"))?;
+        let end = usize::try_from(range.end).map_err(|e| err!(with: e, "Range would exceed data size"))?;
+        let subdata = self.get(start..end).ok_or_else(|| err!("Range would exceed data size"))?;
+        Ok(Source::from(subdata.to_vec()))
+    }
+}
+
+/// Derives an entity tag for a file from its length and modification time
+pub(crate) fn file_etag(metadata: &Metadata) -> Option<Data> {
+    let modified = metadata.modified().ok()?;
+    let modified = modified.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let etag = format!(r#""{:x}-{:x}.{:x}""#, metadata.len(), modified.as_secs(), modified.subsec_nanos());
+    Some(Data::from(etag))
+}
+
+/// Gets the size of the given file
+///
+/// # Note
+/// Block devices report a length of `0`, so their size is determined by seeking to the end instead. Character devices,
+/// FIFOs and sockets have no meaningful size and are rejected.
+pub(crate) fn file_size(file: &File, metadata: &Metadata) -> Result<u64, Error> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+
+        // Check the file type
+        let file_type = metadata.file_type();
+        if file_type.is_char_device() {
+            return Err(err!("Cannot serve ranges of character devices"));
+        }
+        if file_type.is_fifo() {
+            return Err(err!("Cannot serve ranges of FIFOs"));
+        }
+        if file_type.is_socket() {
+            return Err(err!("Cannot serve ranges of sockets"));
+        }
+        if file_type.is_block_device() {
+            // Seek to the end and restore the original position
+            let mut file = file;
+            let position = file.stream_position()?;
+            let size = file.seek(SeekFrom::End(0))?;
+            file.seek(SeekFrom::Start(position))?;
+            return Ok(size);
+        }
+    }
+    Ok(metadata.len())
+}
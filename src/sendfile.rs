@@ -0,0 +1,160 @@
+//! Zero-copy transmission of file ranges via `sendfile(2)`
+
+use crate::rangeext::RangeExt;
+use crate::rangeresponse::RangeResponse;
+use crate::{positional, representation};
+use ehttpd::bytes::Source;
+use ehttpd::err;
+use ehttpd::error::Error;
+use ehttpd::http::Response;
+use std::fs::File;
+use std::io::{self, ErrorKind, Read, Write};
+use std::mem::MaybeUninit;
+use std::ops::{Range, RangeBounds};
+use std::os::fd::{AsFd, AsRawFd};
+
+/// The maximum amount of bytes to transfer with a single `sendfile` call
+const SENDFILE_MAX: u64 = 0x7fff_f000;
+
+/// A file range body that carries the file descriptor, offset and length so it can be moved to a socket via
+/// `sendfile(2)`
+///
+/// # Fallback
+/// If the sink is not a socket, the range is copied through userspace instead. The body also implements `Read` via
+/// positional reads, so it can be used like any other body.
+#[derive(Debug)]
+pub struct SendfileBody {
+    /// The underlying file
+    file: File,
+    /// The current offset within the file
+    offset: u64,
+    /// The remaining bytes to transmit
+    remaining: u64,
+    /// The total file size
+    total: u64,
+}
+impl SendfileBody {
+    /// Creates a new body over the given range of the file
+    pub fn new<T, R>(file: T, range: R) -> Result<Self, Error>
+    where
+        T: Into<File>,
+        R: RangeBounds<u64>,
+    {
+        // Get the file size and validate the range
+        let file: File = file.into();
+        let total = representation::file_size(&file, &file.metadata()?)?;
+        let Range { start, end } =
+            Range::from_range_bounds(range, 0, total).ok_or_else(|| err!("Range would exceed file size"))?;
+        Ok(Self { file, offset: start, remaining: end.saturating_sub(start), total })
+    }
+
+    /// The remaining bytes to transmit
+    pub const fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Sets the `Content-Range` and `Content-Length` headers of the given response for the remaining range
+    pub fn set_headers(&self, response: &mut Response) -> Result<(), Error> {
+        let end = self.offset.saturating_add(self.remaining);
+        response.set_content_range(self.offset..end, self.total)?;
+        response.set_content_length(self.remaining);
+        Ok(())
+    }
+
+    /// Transmits the remaining range to the given sink and returns the amount of bytes transmitted
+    ///
+    /// # Note
+    /// This function uses `sendfile(2)` if the sink is a socket, and falls back to a userspace copy otherwise. Since the
+    /// bytes are moved directly to the socket, any userspace buffer around the sink must be flushed before.
+    pub fn transmit<S>(&mut self, sink: &mut S) -> Result<u64, Error>
+    where
+        S: Write + AsFd,
+    {
+        // Fall back to the copy path if the sink is not a socket
+        if !is_socket(sink)? {
+            let copied = io::copy(self, sink)?;
+            return Ok(copied);
+        }
+
+        // Send the file range
+        let mut transmitted = 0;
+        while self.remaining > 0 {
+            // Compute the chunk size and prepare the offset
+            let count = self.remaining.min(SENDFILE_MAX) as usize;
+            let mut offset =
+                libc::off_t::try_from(self.offset).map_err(|e| err!(with: e, "File offset is too large"))?;
+
+            // SAFETY: Both file descriptors are valid for the duration of the call
+            let sent = unsafe { libc::sendfile(sink.as_fd().as_raw_fd(), self.file.as_raw_fd(), &mut offset, count) };
+            match sent {
+                0 => return Err(err!(with: io::Error::from(ErrorKind::UnexpectedEof), "File has been truncated")),
+                ..0 if io::Error::last_os_error().kind() == ErrorKind::Interrupted => continue,
+                ..0 => return Err(io::Error::last_os_error().into()),
+                sent => {
+                    // Advance the range
+                    self.offset += sent as u64;
+                    self.remaining -= sent as u64;
+                    transmitted += sent as u64;
+                }
+            }
+        }
+        Ok(transmitted)
+    }
+}
+impl Read for SendfileBody {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Read the next chunk via a positional read
+        let to_read = (buf.len() as u64).min(self.remaining) as usize;
+        let read = positional::read_at(&self.file, &mut buf[..to_read], self.offset)?;
+        if read == 0 && to_read > 0 {
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, "file has been truncated"));
+        }
+
+        // Advance the range
+        self.offset += read as u64;
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+/// Writes the response header to the given sink and transmits the body via `sendfile(2)` if possible
+///
+/// # Note
+/// Any body already set on `response` is discarded. This function also sets the `Content-Range` and the
+/// `Content-Length` headers and raises an error if `response.status` is not `206`
+pub fn to_stream<S>(response: &mut Response, mut body: SendfileBody, sink: &mut S) -> Result<(), Error>
+where
+    S: Write + AsFd,
+{
+    // Ensure that we are a 206
+    if !response.status.eq(b"206") {
+        return Err(err!("Response is not a 206 response"));
+    }
+
+    // Write the header and flush it before moving the body
+    body.set_headers(response)?;
+    response.body = Source::default();
+    response.to_stream(sink)?;
+    sink.flush()?;
+
+    // Transmit the body
+    body.transmit(sink)?;
+    Ok(())
+}
+
+/// Checks whether the given file descriptor refers to a socket
+fn is_socket<T>(fd: &T) -> Result<bool, Error>
+where
+    T: AsFd,
+{
+    let mut stat = MaybeUninit::<libc::stat>::uninit();
+    // SAFETY: The file descriptor is valid and `stat` points to writeable memory of the correct size
+    let result = unsafe { libc::fstat(fd.as_fd().as_raw_fd(), stat.as_mut_ptr()) };
+    if result != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+
+    // SAFETY: `fstat` has succeeded, so `stat` is initialized
+    let stat = unsafe { stat.assume_init() };
+    Ok(stat.st_mode & libc::S_IFMT == libc::S_IFSOCK)
+}
@@ -0,0 +1,95 @@
+//! Extent-mapped sparse representations with zero-filled holes
+
+use crate::concat::{Concat, Segment};
+use crate::representation::Representation;
+use ehttpd::bytes::{Data, Source};
+use ehttpd::err;
+use ehttpd::error::Error;
+use std::fs::File;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// A data extent within a sparse representation
+#[derive(Debug, Clone)]
+pub struct Extent {
+    /// The offset of the extent within the representation
+    pub offset: u64,
+    /// The backing file
+    pub file: Arc<File>,
+    /// The offset of the extent within the backing file
+    pub file_offset: u64,
+    /// The extent length
+    pub len: u64,
+}
+
+/// A sparse representation that is described by a list of data extents, where the holes between them read as zeros
+///
+/// # Note
+/// Holes are generated on the fly and never read from disk.
+#[derive(Debug, Clone)]
+pub struct Sparse {
+    /// The underlying concatenation of extents and holes
+    concat: Concat,
+}
+impl Sparse {
+    /// Creates a new sparse representation with the given total length from the given data extents
+    ///
+    /// # Note
+    /// The extents must be sorted by offset, must not overlap and must not exceed the total length or their backing file.
+    pub fn new<I>(len: u64, extents: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = Extent>,
+    {
+        // Map the extents and holes onto segments
+        let (mut segments, mut position) = (Vec::new(), 0u64);
+        for extent in extents {
+            // Validate the extent
+            let Some(end) = extent.offset.checked_add(extent.len) else {
+                return Err(err!("Extent is too large"));
+            };
+            let Some(file_end) = extent.file_offset.checked_add(extent.len) else {
+                return Err(err!("Extent is too large"));
+            };
+            if extent.offset < position || end > len {
+                return Err(err!("Extents are unordered, overlapping or exceed the total length"));
+            }
+
+            // Insert the hole before the extent and the extent itself
+            if extent.offset > position {
+                segments.push(Segment::Fill { byte: 0, len: extent.offset - position });
+            }
+            segments.push(Segment::File { file: extent.file, range: extent.file_offset..file_end });
+            position = end;
+        }
+
+        // Insert the trailing hole
+        if len > position {
+            segments.push(Segment::Fill { byte: 0, len: len - position });
+        }
+        Ok(Self { concat: Concat::new(segments)? })
+    }
+    /// Sets the entity tag of the sparse representation
+    pub fn with_etag(self, etag: Data) -> Self {
+        Self { concat: self.concat.with_etag(etag) }
+    }
+    /// Sets the content type of the sparse representation
+    pub fn with_content_type(self, content_type: Data) -> Self {
+        Self { concat: self.concat.with_content_type(content_type) }
+    }
+}
+impl Representation for Sparse {
+    fn complete_length(&self) -> Result<Option<u64>, Error> {
+        self.concat.complete_length()
+    }
+
+    fn etag(&self) -> Result<Option<Data>, Error> {
+        self.concat.etag()
+    }
+    fn content_type(&self) -> Option<Data> {
+        self.concat.content_type()
+    }
+
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error> {
+        self.concat.open_range(range)
+    }
+}
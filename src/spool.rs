@@ -0,0 +1,288 @@
+//! Spooling of non-seekable content to make it range-servable
+
+use crate::positional::PositionalReader;
+use crate::representation::Representation;
+use ehttpd::bytes::{Data, Source};
+use ehttpd::err;
+use ehttpd::error::Error;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::{env, process};
+
+/// Options for spooled content
+#[derive(Debug, Clone)]
+pub struct SpoolOptions {
+    /// Content up to this size is spooled into memory, larger content is spooled into a temporary file
+    pub memory_threshold: u64,
+    /// The duration after which a spool expires; a spool never expires if the expiry time is not representable (e.g.
+    /// [`Duration::MAX`])
+    pub expiry: Duration,
+    /// The directory for temporary files
+    pub dir: PathBuf,
+    /// The content type of the spooled content if known
+    pub content_type: Option<Data>,
+}
+impl Default for SpoolOptions {
+    fn default() -> Self {
+        Self {
+            memory_threshold: 1024 * 1024,
+            expiry: Duration::from_secs(3600),
+            dir: env::temp_dir(),
+            content_type: None,
+        }
+    }
+}
+
+/// The spool storage
+#[derive(Debug)]
+enum Storage {
+    /// In-memory storage
+    Memory(Data),
+    /// A temporary file which is deleted once the spool is dropped
+    File {
+        /// The shared file handle
+        file: Arc<File>,
+        /// The file path
+        path: PathBuf,
+    },
+}
+impl Drop for Storage {
+    fn drop(&mut self) {
+        if let Self::File { path, .. } = self {
+            // Note: The cleanup is best-effort, so errors are ignored
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// The shared spool state
+#[derive(Debug)]
+struct SpoolInner {
+    /// The spooled content
+    storage: Storage,
+    /// The content length
+    len: u64,
+    /// The entity tag derived from the content
+    etag: Data,
+    /// The content type if known
+    content_type: Option<Data>,
+    /// When the spool expires if ever
+    expires: Option<Instant>,
+}
+
+/// Generated content that has been spooled once into memory or a temporary file, so that it can be served with ranges
+/// and a stable entity tag
+#[derive(Debug, Clone)]
+pub struct Spool {
+    /// The shared spool state
+    inner: Arc<SpoolInner>,
+}
+impl Spool {
+    /// Spools the given stream
+    pub fn new<T>(mut reader: T, options: &SpoolOptions) -> Result<Self, Error>
+    where
+        T: Read,
+    {
+        // Read up to the memory threshold
+        let mut hasher = Fnv1a::default();
+        let mut buf = Vec::new();
+        (&mut reader).take(options.memory_threshold.saturating_add(1)).read_to_end(&mut buf)?;
+        hasher.write_all(&buf)?;
+
+        // Spool into a temporary file if the threshold has been exceeded
+        let (storage, len) = match buf.len() as u64 > options.memory_threshold {
+            true => Self::spool_file(buf, reader, &mut hasher, options)?,
+            false => {
+                let len = buf.len() as u64;
+                (Storage::Memory(Data::from(buf)), len)
+            }
+        };
+
+        // Init self
+        let etag = Data::from(format!(r#""{len:x}-{:016x}""#, hasher.0));
+        let expires = Instant::now().checked_add(options.expiry);
+        let inner = SpoolInner { storage, len, etag, content_type: options.content_type.clone(), expires };
+        Ok(Self { inner: Arc::new(inner) })
+    }
+
+    /// Whether the spool is held in memory or not
+    pub fn is_in_memory(&self) -> bool {
+        matches!(self.inner.storage, Storage::Memory(_))
+    }
+    /// Whether the spool has expired or not
+    pub fn is_expired(&self) -> bool {
+        self.inner.expires.is_some_and(|expires| Instant::now() >= expires)
+    }
+
+    /// Spools the already-read prefix and the remaining stream into a temporary file
+    fn spool_file<T>(
+        prefix: Vec<u8>,
+        mut reader: T,
+        hasher: &mut Fnv1a,
+        options: &SpoolOptions,
+    ) -> Result<(Storage, u64), Error>
+    where
+        T: Read,
+    {
+        /// A counter to create unique file names
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        // Create the temporary file
+        let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = options.dir.join(format!("ehttpd-range.spool.{}.{counter}.tmp", process::id()));
+        let file = File::options().read(true).write(true).create_new(true).open(&path)?;
+        let storage = Storage::File { file: Arc::new(file), path };
+        let Storage::File { file, .. } = &storage else {
+            unreachable!("storage is a file storage");
+        };
+
+        // Write the prefix and the remaining stream while hashing the remaining stream
+        let mut writer = BufWriter::new(file.as_ref());
+        writer.write_all(&prefix)?;
+        let mut tee = Tee { writer: &mut writer, hasher };
+        let copied = io::copy(&mut reader, &mut tee)?;
+        writer.flush()?;
+        drop(writer);
+        Ok((storage, prefix.len() as u64 + copied))
+    }
+}
+impl Representation for Spool {
+    fn complete_length(&self) -> Result<Option<u64>, Error> {
+        Ok(Some(self.inner.len))
+    }
+
+    fn etag(&self) -> Result<Option<Data>, Error> {
+        Ok(Some(self.inner.etag.clone()))
+    }
+    fn content_type(&self) -> Option<Data> {
+        self.inner.content_type.clone()
+    }
+
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error> {
+        // Validate the range
+        if range.start > range.end || range.end > self.inner.len {
+            return Err(err!("Range would exceed spool size"));
+        }
+
+        // Open the range
+        match &self.inner.storage {
+            Storage::Memory(data) => {
+                let view =
+                    data.subcopy(range.start as usize..range.end as usize).expect("range would exceed spool size");
+                Ok(Source::from(view))
+            }
+            Storage::File { file, .. } => {
+                let reader = PositionalReader::new(file.clone(), range);
+                Ok(Source::new(BufReader::new(reader)))
+            }
+        }
+    }
+}
+
+/// A keyed store of spools that purges expired spools
+///
+/// # Purging
+/// The store has no background task; expired spools are only removed by [`Self::get_or_spool`] and
+/// [`Self::purge_expired`]. If the store is not accessed regularly, callers should call [`Self::purge_expired`]
+/// periodically to release the spool storage.
+#[derive(Debug, Default)]
+pub struct SpoolStore {
+    /// The spools by key
+    spools: Mutex<HashMap<String, Spool>>,
+    /// The spool options
+    options: SpoolOptions,
+}
+impl SpoolStore {
+    /// Creates a new spool store with the given options
+    pub fn new(options: SpoolOptions) -> Self {
+        Self { spools: Mutex::default(), options }
+    }
+
+    /// Gets the spool for the given key, or spools the stream from `generate` if there is no valid spool
+    ///
+    /// # Note
+    /// The content is generated without holding the store lock, so concurrent requests for the same missing key may
+    /// generate the content more than once; the last spool wins.
+    pub fn get_or_spool<F, T>(&self, key: &str, generate: F) -> Result<Spool, Error>
+    where
+        F: FnOnce() -> Result<T, Error>,
+        T: Read,
+    {
+        // Return the existing spool if it is still valid
+        self.purge_expired();
+        if let Some(spool) = self.get(key) {
+            return Ok(spool);
+        }
+
+        // Spool the content
+        let spool = Spool::new(generate()?, &self.options)?;
+        let mut spools = self.spools.lock().expect("spool store lock is poisoned");
+        spools.insert(key.to_string(), spool.clone());
+        Ok(spool)
+    }
+    /// Gets the spool for the given key if it exists and has not expired
+    pub fn get(&self, key: &str) -> Option<Spool> {
+        let spools = self.spools.lock().expect("spool store lock is poisoned");
+        spools.get(key).filter(|spool| !spool.is_expired()).cloned()
+    }
+    /// Removes the spool for the given key
+    ///
+    /// # Note
+    /// The spool storage is cleaned up once the last in-flight response that uses the spool has finished
+    pub fn remove(&self, key: &str) -> Option<Spool> {
+        let mut spools = self.spools.lock().expect("spool store lock is poisoned");
+        spools.remove(key)
+    }
+    /// Removes all expired spools
+    pub fn purge_expired(&self) {
+        let mut spools = self.spools.lock().expect("spool store lock is poisoned");
+        spools.retain(|_, spool| !spool.is_expired());
+    }
+}
+
+/// A 64 bit FNV-1a hasher which is stable across platforms and compiler versions
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Fnv1a(pub u64);
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+impl Write for Fnv1a {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for byte in buf {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A writer that forwards all bytes to a writer and a hasher
+struct Tee<'a, W> {
+    /// The writer
+    writer: &'a mut W,
+    /// The hasher
+    hasher: &'a mut Fnv1a,
+}
+impl<W> Write for Tee<'_, W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.hasher.write_all(&buf[..written])?;
+        Ok(written)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
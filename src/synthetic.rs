@@ -0,0 +1,157 @@
+//! Synthetic bodies that are generated on the fly
+
+use crate::representation::Representation;
+use crate::spool::Fnv1a;
+use ehttpd::bytes::{Data, Source};
+use ehttpd::err;
+use ehttpd::error::Error;
+use std::io::{self, Read, Write};
+use std::ops::Range;
+
+/// A generator for synthetic content
+#[derive(Debug, Clone)]
+pub enum Generator {
+    /// Zero bytes
+    Zeros,
+    /// A repeating byte pattern
+    Pattern(Data),
+    /// A deterministic pseudo-random stream keyed by a seed
+    Random(u64),
+}
+impl Generator {
+    /// Generates the content at the given absolute offset into the buffer
+    fn generate(&self, offset: u64, buf: &mut [u8]) {
+        match self {
+            Self::Zeros => buf.fill(0),
+            Self::Pattern(pattern) => {
+                // Copy the pattern starting at the phase of the offset
+                let mut phase = (offset % pattern.len() as u64) as usize;
+                for chunk in buf.chunks_mut(pattern.len()) {
+                    let (head, tail) = chunk.split_at_mut(chunk.len().min(pattern.len() - phase));
+                    head.copy_from_slice(&pattern[phase..phase + head.len()]);
+                    tail.copy_from_slice(&pattern[..tail.len()]);
+                    phase = (phase + chunk.len()) % pattern.len();
+                }
+            }
+            Self::Random(seed) => {
+                // Generate each 8-byte block from its index, so that any offset can be produced in constant time
+                let (key, mut position, mut buf) = (splitmix64(*seed), offset, buf);
+                while !buf.is_empty() {
+                    let block = splitmix64(key.wrapping_add(position / 8)).to_le_bytes();
+                    let phase = (position % 8) as usize;
+                    let len = buf.len().min(8 - phase);
+                    buf[..len].copy_from_slice(&block[phase..phase + len]);
+                    (position, buf) = (position + len as u64, &mut buf[len..]);
+                }
+            }
+        }
+    }
+}
+
+/// A synthetic representation of the given length that is generated on the fly without any storage
+#[derive(Debug, Clone)]
+pub struct Synthetic {
+    /// The content generator
+    generator: Generator,
+    /// The content length
+    len: u64,
+    /// The content type if any
+    content_type: Option<Data>,
+}
+impl Synthetic {
+    /// Creates a new synthetic representation that consists of zero bytes
+    pub const fn zeros(len: u64) -> Self {
+        Self { generator: Generator::Zeros, len, content_type: None }
+    }
+    /// Creates a new synthetic representation that repeats the given non-empty pattern
+    pub fn pattern<T>(pattern: T, len: u64) -> Result<Self, Error>
+    where
+        T: Into<Data>,
+    {
+        let pattern = pattern.into();
+        if pattern.is_empty() {
+            return Err(err!("Pattern is empty"));
+        }
+        Ok(Self { generator: Generator::Pattern(pattern), len, content_type: None })
+    }
+    /// Creates a new synthetic representation that consists of a deterministic pseudo-random stream keyed by `seed`
+    ///
+    /// # Warning
+    /// The stream is not cryptographically secure.
+    pub const fn random(seed: u64, len: u64) -> Self {
+        Self { generator: Generator::Random(seed), len, content_type: None }
+    }
+    /// Sets the content type of the synthetic representation
+    pub fn with_content_type(mut self, content_type: Data) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// The content generator
+    pub const fn generator(&self) -> &Generator {
+        &self.generator
+    }
+}
+impl Representation for Synthetic {
+    fn complete_length(&self) -> Result<Option<u64>, Error> {
+        Ok(Some(self.len))
+    }
+
+    fn etag(&self) -> Result<Option<Data>, Error> {
+        // Derive the entity tag from the generator parameters
+        let etag = match &self.generator {
+            Generator::Zeros => format!(r#""zeros-{:x}""#, self.len),
+            Generator::Pattern(pattern) => {
+                let mut hasher = Fnv1a::default();
+                hasher.write_all(pattern)?;
+                format!(r#""pattern-{:016x}-{:x}""#, hasher.0, self.len)
+            }
+            Generator::Random(seed) => format!(r#""random-{seed:016x}-{:x}""#, self.len),
+        };
+        Ok(Some(Data::from(etag)))
+    }
+    fn content_type(&self) -> Option<Data> {
+        self.content_type.clone()
+    }
+
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error> {
+        // Validate the range
+        if range.start > range.end || range.end > self.len {
+            return Err(err!("Range would exceed synthetic size"));
+        }
+
+        // Create the reader
+        let reader = SyntheticReader { generator: self.generator.clone(), position: range.start, end: range.end };
+        Ok(Source::new(reader))
+    }
+}
+
+/// A reader over a range of synthetic content
+#[derive(Debug)]
+struct SyntheticReader {
+    /// The content generator
+    generator: Generator,
+    /// The current position
+    position: u64,
+    /// The end of the range
+    end: u64,
+}
+impl Read for SyntheticReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Generate the next chunk
+        let to_read = (buf.len() as u64).min(self.end - self.position) as usize;
+        self.generator.generate(self.position, &mut buf[..to_read]);
+
+        // Advance the position
+        self.position += to_read as u64;
+        Ok(to_read)
+    }
+}
+
+/// The `splitmix64` mixing function
+const fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
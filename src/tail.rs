@@ -0,0 +1,96 @@
+//! Tail-mode bodies that keep following a growing file
+
+use crate::guarded::FileChangedError;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The stop conditions and polling behavior of a tail-mode body
+#[derive(Debug, Clone)]
+pub struct TailOptions {
+    /// The interval to poll the file for new data
+    pub poll_interval: Duration,
+    /// The body ends if no new data has been appended within this duration after the end of the file has been reached
+    pub idle_timeout: Duration,
+    /// The body ends after this amount of bytes if any
+    pub max_len: Option<u64>,
+    /// The body ends once this flag is set if any
+    pub stop: Option<Arc<AtomicBool>>,
+}
+impl Default for TailOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_millis(250),
+            idle_timeout: Duration::from_secs(30),
+            max_len: None,
+            stop: None,
+        }
+    }
+}
+
+/// A reader that starts at an offset and keeps following a growing file until a stop condition is met
+#[derive(Debug)]
+pub struct TailReader {
+    /// The underlying file
+    file: File,
+    /// The current position within the file
+    position: u64,
+    /// The remaining bytes until `max_len` is reached
+    remaining: Option<u64>,
+    /// The options
+    options: TailOptions,
+}
+impl TailReader {
+    /// Creates a new tail reader that starts at the given offset
+    pub fn new(mut file: File, start: u64, options: TailOptions) -> io::Result<Self> {
+        file.seek(SeekFrom::Start(start))?;
+        Ok(Self { file, position: start, remaining: options.max_len, options })
+    }
+
+    /// Checks whether the stop flag is set
+    fn is_stopped(&self) -> bool {
+        (self.options.stop.as_ref()).is_some_and(|stop| stop.load(Ordering::Relaxed))
+    }
+}
+impl Read for TailReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Limit the buffer to the remaining length
+        let to_read = match self.remaining {
+            Some(remaining) => (buf.len() as u64).min(remaining) as usize,
+            None => buf.len(),
+        };
+        if to_read == 0 {
+            // Either the buffer is empty or `max_len` has been reached
+            return Ok(0);
+        }
+
+        // Read until new data is available or a stop condition is met
+        // Note: The idle timeout only covers the time spent waiting for new data, so slow consumers are not cut off
+        let mut idle_since = None;
+        while !self.is_stopped() {
+            let read = self.file.read(&mut buf[..to_read])?;
+            if read > 0 {
+                // Advance the position
+                self.position += read as u64;
+                self.remaining = self.remaining.map(|remaining| remaining - read as u64);
+                return Ok(read);
+            }
+
+            // Ensure the file has not been truncated below our position
+            if self.file.metadata()?.len() < self.position {
+                return Err(FileChangedError::Truncated.into());
+            }
+
+            // Wait for new data until the idle timeout is reached
+            let idle_since = *idle_since.get_or_insert_with(Instant::now);
+            if idle_since.elapsed() >= self.options.idle_timeout {
+                break;
+            }
+            thread::sleep(self.options.poll_interval);
+        }
+        Ok(0)
+    }
+}
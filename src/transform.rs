@@ -0,0 +1,105 @@
+//! Position-aware stream transforms, e.g. to decrypt seekable ciphers on the fly
+
+use crate::representation::Representation;
+use ehttpd::bytes::{Data, Source};
+use ehttpd::error::Error;
+use std::fmt::Debug;
+use std::io::{self, Read};
+use std::ops::Range;
+use std::time::SystemTime;
+
+/// A transform that depends only on the absolute offset of the bytes, e.g. a counter-mode cipher
+pub trait Transform {
+    /// Transforms the buffer in place, where `offset` is the absolute offset of the first byte within the stream
+    fn apply(&mut self, offset: u64, buf: &mut [u8]);
+}
+
+/// A reader that applies a transform to all bytes read from the underlying reader
+#[derive(Debug)]
+pub struct TransformReader<T, X> {
+    /// The underlying reader
+    reader: T,
+    /// The transform
+    transform: X,
+    /// The absolute offset of the next byte
+    offset: u64,
+}
+impl<T, X> TransformReader<T, X> {
+    /// Creates a new transforming reader where `offset` is the absolute offset of the first byte read from `reader`
+    pub const fn new(reader: T, transform: X, offset: u64) -> Self {
+        Self { reader, transform, offset }
+    }
+
+    /// The absolute offset of the next byte
+    pub const fn offset(&self) -> u64 {
+        self.offset
+    }
+    /// Returns the underlying reader
+    pub fn into_inner(self) -> T {
+        self.reader
+    }
+}
+impl<T, X> Read for TransformReader<T, X>
+where
+    T: Read,
+    X: Transform,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Read and transform the next chunk
+        let read = self.reader.read(buf)?;
+        self.transform.apply(self.offset, &mut buf[..read]);
+
+        // Advance the offset
+        self.offset += read as u64;
+        Ok(read)
+    }
+}
+
+/// A representation whose ranges are transformed on the fly, e.g. an encrypted-at-rest file that is served decrypted
+///
+/// # Note
+/// The transform must preserve the length, so that all range arithmetic still applies to the underlying
+/// representation. The metadata, including the entity tag, is taken from the underlying representation.
+#[derive(Debug, Clone)]
+pub struct Transformed<T, X> {
+    /// The underlying representation
+    inner: T,
+    /// The transform
+    transform: X,
+}
+impl<T, X> Transformed<T, X> {
+    /// Creates a new transformed representation
+    pub const fn new(inner: T, transform: X) -> Self {
+        Self { inner, transform }
+    }
+
+    /// The underlying representation
+    pub const fn get_ref(&self) -> &T {
+        &self.inner
+    }
+}
+impl<T, X> Representation for Transformed<T, X>
+where
+    T: Representation,
+    X: Transform + Clone + Debug + Send + Sync + 'static,
+{
+    fn complete_length(&self) -> Result<Option<u64>, Error> {
+        self.inner.complete_length()
+    }
+
+    fn etag(&self) -> Result<Option<Data>, Error> {
+        self.inner.etag()
+    }
+    fn last_modified(&self) -> Result<Option<SystemTime>, Error> {
+        self.inner.last_modified()
+    }
+    fn content_type(&self) -> Option<Data> {
+        self.inner.content_type()
+    }
+
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error> {
+        let source = self.inner.open_range(range.clone())?;
+        let reader = TransformReader::new(source, self.transform.clone(), range.start);
+        Ok(Source::new(reader))
+    }
+}
@@ -0,0 +1,85 @@
+use ehttpd::bytes::Source;
+use ehttpd::http::{Request, Response};
+use ehttpd_range::backend::{FsBackend, MemoryBackend};
+use ehttpd_range::handler;
+use std::path::Path;
+
+/// Performs a request against the given backend and returns the serialized response
+fn roundtrip(backend: &MemoryBackend, request: &str) -> String {
+    // Parse the request
+    let mut request_stream = Source::from(request.to_string());
+    let request: Request =
+        Request::from_stream(&mut request_stream).expect("failed to parse request").expect("unexpected empty request");
+
+    // Serve and serialize the response
+    let mut response: Response = handler::serve(backend, &request);
+    let mut response_data = Vec::new();
+    response.to_stream(&mut response_data).expect("failed to serialize response");
+    String::from_utf8(response_data).expect("response is not valid UTF-8")
+}
+
+#[test]
+fn memory_backend() {
+    let backend = MemoryBackend::new();
+    backend.insert("test/object", b"Testolope");
+
+    // Test full response
+    let response = roundtrip(&backend, "GET /test/object HTTP/1.1\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.ends_with("Content-Length: 9\r\n\r\nTestolope"));
+
+    // Test partial response
+    let response = roundtrip(&backend, "GET /test/object?query HTTP/1.1\r\nRange: bytes=4-\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 206 Partial Content\r\n"));
+    assert!(response.contains("Content-Range: bytes 4-8/9\r\n"));
+    assert!(response.ends_with("\r\n\r\nolope"));
+
+    // Test clamped range end
+    let response = roundtrip(&backend, "GET /test/object HTTP/1.1\r\nRange: bytes=7-100\r\n\r\n");
+    assert!(response.contains("Content-Range: bytes 7-8/9\r\n"));
+    assert!(response.ends_with("\r\n\r\npe"));
+
+    // Test suffix ranges
+    let response = roundtrip(&backend, "GET /test/object HTTP/1.1\r\nRange: bytes=-4\r\n\r\n");
+    assert!(response.contains("Content-Range: bytes 5-8/9\r\n"));
+    assert!(response.ends_with("\r\n\r\nlope"));
+    let response = roundtrip(&backend, "GET /test/object HTTP/1.1\r\nRange: bytes=-100\r\n\r\n");
+    assert!(response.contains("Content-Range: bytes 0-8/9\r\n"));
+    let response = roundtrip(&backend, "GET /test/object HTTP/1.1\r\nRange: bytes=-0\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 416 Range Not Satisfiable\r\n"));
+
+    // Test HEAD request
+    let response = roundtrip(&backend, "HEAD /test/object HTTP/1.1\r\nRange: bytes=0-3\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 206 Partial Content\r\n"));
+    assert!(response.ends_with("Content-Length: 4\r\n\r\n"));
+}
+
+#[test]
+fn memory_backend_errors() {
+    let backend = MemoryBackend::new();
+    backend.insert("object", b"Testolope");
+
+    // Test unsatisfiable range
+    let response = roundtrip(&backend, "GET /object HTTP/1.1\r\nRange: bytes=9-\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 416 Range Not Satisfiable\r\n"));
+    assert!(response.contains("Content-Range: bytes */9\r\n"));
+
+    // Test missing object and invalid method
+    let response = roundtrip(&backend, "GET /missing HTTP/1.1\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    let response = roundtrip(&backend, "POST /object HTTP/1.1\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+
+    // Test removed object
+    backend.remove("object");
+    let response = roundtrip(&backend, "GET /object HTTP/1.1\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+}
+
+#[test]
+fn fs_backend_resolve() {
+    let backend = FsBackend::new("/srv/data");
+    assert_eq!(backend.resolve("a/./b.txt").as_deref(), Some(Path::new("/srv/data/a/b.txt")));
+    assert_eq!(backend.resolve("../etc/passwd"), None);
+    assert_eq!(backend.resolve("/etc/passwd"), None);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use ehttpd::http::Response;
+use ehttpd_range::bufpool::{BufferPool, PooledReader};
+use ehttpd_range::{FileRangeOptions, RangeResponse};
+use std::fs::{self, File};
+use std::io::{BufRead, Read};
+use std::sync::Arc;
+
+#[test]
+fn pooled_reader() {
+    let pool = Arc::new(BufferPool::new(4, 2));
+
+    // Read through a pooled buffer
+    let mut reader = PooledReader::new(b"Testolope".as_slice(), &pool);
+    assert_eq!(reader.fill_buf().expect("failed to fill buffer"), b"Test");
+    reader.consume(2);
+    let mut read = String::new();
+    reader.read_to_string(&mut read).expect("failed to read from reader");
+    assert_eq!(read, "stolope");
+
+    // Ensure that the buffer is returned to the pool
+    assert_eq!(pool.idle(), 0);
+    drop(reader);
+    assert_eq!(pool.idle(), 1);
+
+    // Ensure that the pool does not keep more than `max_idle` buffers
+    let buffers: Vec<_> = (0..3).map(|_| pool.take()).collect();
+    assert_eq!(pool.idle(), 0);
+    drop(buffers);
+    assert_eq!(pool.idle(), 2);
+}
+
+#[test]
+fn file_range_pooled() {
+    // Create the test file
+    let path = common::temppath("ehttpd-range.test-bufpool.tmp", b"Testolope");
+    let pool = Arc::new(BufferPool::new(2, 8));
+    let options = FileRangeOptions { pool: Some(pool.clone()), ..Default::default() };
+
+    // Serve two ranges that borrow from the same pool
+    for _ in 0..2 {
+        let file = File::open(&path).expect("failed to open temp file");
+        let mut response: Response = RangeResponse::new_206_partial_content();
+        response.set_body_file_range_with(file, 2..7, &options).expect("failed to set file range");
+
+        // Read the body and ensure that the buffer is returned on drop
+        let mut body = Vec::new();
+        response.body.read_to_end(&mut body).expect("failed to read body");
+        assert_eq!(body, b"stolo");
+        drop(response);
+        assert_eq!(pool.idle(), 1);
+    }
+    fs::remove_file(&path).expect("failed to delete temp file");
+}
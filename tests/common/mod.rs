@@ -0,0 +1,45 @@
+//! Shared test fixtures
+// Note: Each test crate only uses some of the fixtures
+#![allow(dead_code)]
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+/// Creates a temp file with the given name and contents and returns its path
+pub fn temppath(name: &str, contents: &[u8]) -> PathBuf {
+    let path = env::temp_dir().join(name);
+    fs::write(&path, contents).expect("failed to create temp file");
+    path
+}
+
+/// Creates an anonymous temp file with the given name and contents
+///
+/// # Note
+/// The file is unlinked immediately after it has been opened, so it is cleaned up once the handle is dropped.
+pub fn tempfile(name: &str, contents: &[u8]) -> File {
+    let path = temppath(name, contents);
+    let file = File::open(&path).expect("failed to open temp file");
+    fs::remove_file(&path).expect("failed to delete temp file");
+    file
+}
+
+/// Creates an anonymous sparse temp file with the given name and length that contains `data` at `offset`
+pub fn sparse_tempfile(name: &str, len: u64, offset: u64, data: &[u8]) -> File {
+    let path = env::temp_dir().join(name);
+    let mut file = File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .expect("failed to create temp file");
+    fs::remove_file(&path).expect("failed to delete temp file");
+
+    // Write the data into the otherwise empty file
+    file.set_len(len).expect("failed to resize temp file");
+    file.seek(SeekFrom::Start(offset)).expect("failed to seek within temp file");
+    file.write_all(data).expect("failed to write temp file");
+    file
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use ehttpd::bytes::Data;
+use ehttpd::http::Response;
+use ehttpd_range::concat::{Concat, Segment};
+use ehttpd_range::{RangeResponse, Representation};
+use std::io::Read;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Reads the given range from the representation
+fn read_range(representation: &Concat, start: u64, end: u64) -> Vec<u8> {
+    let mut read = Vec::new();
+    let mut source = representation.open_range(start..end).expect("failed to open range");
+    source.read_to_end(&mut read).expect("failed to read range");
+    read
+}
+
+#[test]
+fn concat_ranges() {
+    let file = Arc::new(common::tempfile("ehttpd-range.test-concat-ranges.tmp", b"xxTestolopexx"));
+    let segments = [
+        Segment::Data(Data::from(b"<head>".to_vec())),
+        Segment::File { file: file.clone(), range: 2..11 },
+        Segment::Data(Data::from(Vec::new())),
+        Segment::Fill { byte: b'.', len: 3 },
+    ];
+    let concat = Concat::new(segments).expect("failed to create concatenation");
+    assert_eq!(concat.complete_length().expect("failed to get length"), Some(18));
+
+    // Read the whole, single segments and ranges across boundaries
+    assert_eq!(read_range(&concat, 0, 18), b"<head>Testolope...");
+    assert_eq!(read_range(&concat, 6, 15), b"Testolope");
+    assert_eq!(read_range(&concat, 4, 8), b"d>Te");
+    assert_eq!(read_range(&concat, 13, 17), b"pe..");
+    assert_eq!(read_range(&concat, 18, 18), b"");
+    assert!(concat.open_range(10..19).is_err());
+
+    // Reject file segments that exceed the file
+    assert!(Concat::new([Segment::File { file: file.clone(), range: 0..100 }]).is_err());
+    assert!(Concat::new([Segment::File { file, range: Range { start: 5, end: 2 } }]).is_err());
+}
+
+#[test]
+fn concat_response() {
+    let segments = [Segment::Data(Data::from(b"Test".to_vec())), Segment::Data(Data::from(b"olope".to_vec()))];
+    let concat = Concat::new(segments).expect("failed to create concatenation");
+
+    // Serve a range across the segment boundary
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_body_range(&concat, 2..=5).expect("failed to set body range");
+    assert!(response.fields.iter().any(|(key, value)| key == "Content-Range" && value == "bytes 2-5/9"));
+
+    // Validate the body
+    let mut body = Vec::new();
+    response.body.read_to_end(&mut body).expect("failed to read body");
+    assert_eq!(body, b"stol");
+}
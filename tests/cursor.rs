@@ -0,0 +1,36 @@
+use ehttpd::bytes::Data;
+use ehttpd_range::cursor::DataCursor;
+use std::io::{BufRead, Read, Seek, SeekFrom};
+
+#[test]
+fn cursor_read_seek() {
+    let mut cursor = DataCursor::new(Data::new(b"Testolope".to_vec()));
+
+    // Read, seek and buffer
+    let mut buf = [0; 4];
+    cursor.read_exact(&mut buf).expect("failed to read from cursor");
+    assert_eq!(&buf, b"Test");
+    assert_eq!(cursor.seek(SeekFrom::Current(3)).expect("failed to seek"), 7);
+    assert_eq!(cursor.fill_buf().expect("failed to fill buffer"), b"pe");
+    assert_eq!(cursor.seek(SeekFrom::End(-5)).expect("failed to seek"), 4);
+
+    // Seek beyond the end and before the start
+    assert_eq!(cursor.seek(SeekFrom::Start(12)).expect("failed to seek"), 12);
+    assert_eq!(cursor.read(&mut buf).expect("failed to read from cursor"), 0);
+    assert!(cursor.seek(SeekFrom::Current(-13)).is_err());
+    assert_eq!(cursor.position(), 12);
+}
+
+#[test]
+fn cursor_read_view() {
+    let data = Data::new(b"Testolope".to_vec());
+    let mut cursor = DataCursor::new(data.clone());
+
+    // Read views that share the underlying buffer
+    cursor.seek(SeekFrom::Start(4)).expect("failed to seek");
+    let view = cursor.read_view(3).expect("failed to read view");
+    assert_eq!(view, b"olo");
+    assert_eq!(view.as_ptr(), data[4..].as_ptr());
+    assert_eq!(cursor.position(), 7);
+    assert!(cursor.read_view(3).is_err());
+}
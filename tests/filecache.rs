@@ -0,0 +1,109 @@
+use ehttpd_range::Representation;
+use ehttpd_range::backend::{Backend, FsBackend};
+use ehttpd_range::filecache::{FileCache, OpenFile};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{env, fs, process};
+
+/// Creates a temp directory with the given files
+fn tempdir(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+    let dir = env::temp_dir().join(format!("ehttpd-range.{name}.{}", process::id()));
+    fs::create_dir_all(&dir).expect("failed to create temp dir");
+    for (name, contents) in files {
+        fs::write(dir.join(name), contents).expect("failed to create temp file");
+    }
+    dir
+}
+
+#[test]
+fn cache_hit_and_invalidate() {
+    let dir = tempdir("test-filecache-hit", &[("a", b"Testolope")]);
+    let cache = FileCache::new(4, Duration::from_secs(3600));
+
+    // Ensure that the handle is reused
+    let first = cache.get(dir.join("a")).expect("failed to open file");
+    let second = cache.get(dir.join("a")).expect("failed to open file");
+    assert!(Arc::ptr_eq(first.file(), second.file()));
+
+    // Replace the file and ensure that the stale metadata is served until the entry is invalidated
+    fs::write(dir.join("a"), b"Test").expect("failed to replace temp file");
+    let stale = cache.get(dir.join("a")).expect("failed to open file");
+    assert_eq!(stale.complete_length().expect("failed to get length"), Some(9));
+    assert!(cache.invalidate(dir.join("a")));
+    let fresh = cache.get(dir.join("a")).expect("failed to open file");
+    assert_eq!(fresh.complete_length().expect("failed to get length"), Some(4));
+    fs::remove_dir_all(&dir).expect("failed to delete temp dir");
+}
+
+#[test]
+fn cache_invalidate_while_opening() {
+    let dir = tempdir("test-filecache-race", &[("a", b"Testolope")]);
+    let cache = FileCache::new(4, Duration::from_secs(3600));
+
+    // Replace and invalidate the file after it has been opened but before it is cached
+    let stale = cache
+        .get_with(dir.join("a"), |path| {
+            let file = OpenFile::open(path)?;
+            fs::write(path, b"Test").expect("failed to replace temp file");
+            assert!(!cache.invalidate(path));
+            Ok(file)
+        })
+        .expect("failed to open file");
+    assert_eq!(stale.complete_length().expect("failed to get length"), Some(9));
+
+    // Ensure that the stale snapshot has not been cached
+    assert!(!cache.contains(dir.join("a")));
+    let fresh = cache.get(dir.join("a")).expect("failed to open file");
+    assert_eq!(fresh.complete_length().expect("failed to get length"), Some(4));
+
+    // Ensure that directory invalidations are respected too
+    assert!(cache.invalidate(dir.join("a")));
+    let _ = cache.get_with(dir.join("a"), |path| {
+        let file = OpenFile::open(path)?;
+        cache.invalidate_dir(&dir);
+        Ok(file)
+    });
+    assert!(!cache.contains(dir.join("a")));
+    fs::remove_dir_all(&dir).expect("failed to delete temp dir");
+}
+
+#[test]
+fn cache_eviction() {
+    let dir = tempdir("test-filecache-eviction", &[("a", b"a"), ("b", b"b"), ("c", b"c")]);
+
+    // Ensure that the least recently used entry is evicted
+    let cache = FileCache::new(2, Duration::from_secs(3600));
+    let _a = cache.get(dir.join("a")).expect("failed to open file");
+    let _b = cache.get(dir.join("b")).expect("failed to open file");
+    let _a = cache.get(dir.join("a")).expect("failed to open file");
+    let _c = cache.get(dir.join("c")).expect("failed to open file");
+    assert!(cache.invalidate(dir.join("a")));
+    assert!(!cache.invalidate(dir.join("b")));
+    assert!(cache.invalidate(dir.join("c")));
+
+    // Ensure that expired entries are not reused
+    let cache = FileCache::new(2, Duration::ZERO);
+    let first = cache.get(dir.join("a")).expect("failed to open file");
+    let second = cache.get(dir.join("a")).expect("failed to open file");
+    assert!(!Arc::ptr_eq(first.file(), second.file()));
+    fs::remove_dir_all(&dir).expect("failed to delete temp dir");
+}
+
+#[test]
+fn fs_backend_cached() {
+    let dir = tempdir("test-filecache-backend", &[("a", b"Testolope")]);
+    fs::create_dir_all(dir.join("dir")).expect("failed to create temp dir");
+
+    // Lookup files via the cache
+    let cache = Arc::new(FileCache::new(4, Duration::from_secs(3600)));
+    let backend = FsBackend::with_cache(&dir, cache.clone());
+    let object = backend.lookup("a").expect("failed to lookup object").expect("missing object");
+    assert_eq!(object.complete_length().expect("failed to get length"), Some(9));
+    assert!(cache.invalidate(dir.join("a")));
+
+    // Ensure that missing files and directories are not served
+    assert!(backend.lookup("missing").expect("failed to lookup object").is_none());
+    assert!(backend.lookup("dir").expect("failed to lookup object").is_none());
+    fs::remove_dir_all(&dir).expect("failed to delete temp dir");
+}
@@ -0,0 +1,55 @@
+use ehttpd::http::Response;
+use ehttpd_range::RangeResponse;
+use ehttpd_range::anyrange::AnyInclusiveRange;
+use ehttpd_range::forward::ForwardSource;
+use std::io::Read;
+
+#[test]
+fn forward_resolve() {
+    // Test known length
+    let source = ForwardSource::new(b"Testolope".as_slice(), Some(9));
+    assert_eq!(source.resolve(AnyInclusiveRange::From { start: 4 }).expect("failed to resolve range"), 4..9);
+    assert_eq!(source.resolve(AnyInclusiveRange::FromTo { start: 1, end: 2 }).expect("failed to resolve range"), 1..3);
+    assert!(source.resolve(AnyInclusiveRange::FromTo { start: 1, end: 9 }).is_err());
+    assert_eq!(source.resolve(AnyInclusiveRange::To { end: 4 }).expect("failed to resolve range"), 5..9);
+    assert_eq!(source.resolve(AnyInclusiveRange::To { end: 100 }).expect("failed to resolve range"), 0..9);
+    assert!(source.resolve(AnyInclusiveRange::To { end: 0 }).is_err());
+
+    // Test unknown length
+    let source = ForwardSource::new(b"Testolope".as_slice(), None);
+    assert_eq!(source.resolve(AnyInclusiveRange::FromTo { start: 1, end: 2 }).expect("failed to resolve range"), 1..3);
+    assert!(source.resolve(AnyInclusiveRange::To { end: 4 }).is_err());
+    assert!(source.resolve(AnyInclusiveRange::From { start: 4 }).is_err());
+    assert!(source.resolve(AnyInclusiveRange::Full).is_err());
+}
+
+#[test]
+fn forward_ranges() {
+    // Read ascending ranges including an empty one
+    let source = ForwardSource::new(b"Testolope".as_slice(), Some(9));
+    let mut reader = source.into_ranges(&[0..2, 3..3, 4..6, 8..9]).expect("failed to create reader");
+    let mut read = String::new();
+    reader.read_to_string(&mut read).expect("failed to read ranges");
+    assert_eq!(read, "Teole");
+
+    // Test unordered and too long ranges
+    let source = ForwardSource::new(b"Testolope".as_slice(), Some(9));
+    assert!(source.into_ranges(&[4..6, 0..2]).is_err());
+    let mut reader = ForwardSource::new(b"Testolope".as_slice(), None).into_range(8..12);
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn forward_response() {
+    // Serve a range of a stream with unknown length
+    let source = ForwardSource::new(b"Testolope".as_slice(), None);
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    let range = AnyInclusiveRange::FromTo { start: 4, end: 6 };
+    response.set_body_forward_range(source, range).expect("failed to set forward range");
+    assert!(response.fields.iter().any(|(key, value)| key == "Content-Range" && value == "bytes 4-6/*"));
+
+    // Validate the body
+    let mut body = Vec::new();
+    response.body.read_to_end(&mut body).expect("failed to read body");
+    assert_eq!(body, b"olo");
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use ehttpd::http::Response;
+use ehttpd_range::guarded::{FileChangedError, FileSnapshot, GuardedReader};
+use ehttpd_range::{FileRangeOptions, RangeResponse};
+use std::fs::{self, File};
+use std::io::Read;
+
+#[test]
+fn guarded_unchanged() {
+    let path = common::temppath("ehttpd-range.test-guarded-unchanged.tmp", b"Testolope");
+
+    // Serve a guarded range
+    let file = File::open(&path).expect("failed to open temp file");
+    let options = FileRangeOptions { guard_interval: Some(2), ..Default::default() };
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_body_file_range_with(file, 1..8, &options).expect("failed to set file range");
+
+    // Validate the body
+    let mut body = Vec::new();
+    response.body.read_to_end(&mut body).expect("failed to read body");
+    assert_eq!(body, b"estolop");
+    fs::remove_file(&path).expect("failed to delete temp file");
+}
+
+#[test]
+fn guarded_truncated() {
+    let path = common::temppath("ehttpd-range.test-guarded-truncated.tmp", b"Testolope");
+
+    // Open a guarded reader and truncate the file
+    let file = File::open(&path).expect("failed to open temp file");
+    let snapshot = FileSnapshot::new(&file.metadata().expect("failed to get metadata"));
+    let mut reader = GuardedReader::new(file, snapshot, 0..9, 1024).expect("failed to create reader");
+    fs::write(&path, b"Test").expect("failed to truncate temp file");
+
+    // Ensure that the reader fails with a distinct error
+    let mut body = Vec::new();
+    let error = reader.read_to_end(&mut body).expect_err("unexpected success on truncated file");
+    assert_eq!(FileChangedError::from_io_error(&error), Some(FileChangedError::Truncated));
+    fs::remove_file(&path).expect("failed to delete temp file");
+}
+
+#[test]
+fn guarded_modified() {
+    let path = common::temppath("ehttpd-range.test-guarded-modified.tmp", b"Testolope");
+
+    // Open a guarded reader and append to the file
+    let file = File::open(&path).expect("failed to open temp file");
+    let snapshot = FileSnapshot::new(&file.metadata().expect("failed to get metadata"));
+    let mut reader = GuardedReader::new(file, snapshot, 0..4, 1024).expect("failed to create reader");
+    fs::write(&path, b"Testolope and more").expect("failed to modify temp file");
+
+    // Ensure that the reader fails with a distinct error
+    let mut body = Vec::new();
+    let error = reader.read_to_end(&mut body).expect_err("unexpected success on modified file");
+    assert_eq!(FileChangedError::from_io_error(&error), Some(FileChangedError::Modified));
+    fs::remove_file(&path).expect("failed to delete temp file");
+}
+
+#[test]
+fn guarded_replaced() {
+    let path = common::temppath("ehttpd-range.test-guarded-replaced.tmp", b"Testolope");
+    let replacement = common::temppath("ehttpd-range.test-guarded-replacement.tmp", b"Other");
+
+    // Open a guarded reader and replace the file via its path
+    let file = File::open(&path).expect("failed to open temp file");
+    let snapshot = FileSnapshot::new(&file.metadata().expect("failed to get metadata"));
+    let mut reader = GuardedReader::new(file, snapshot, 0..9, 1).expect("failed to create reader");
+    fs::rename(&replacement, &path).expect("failed to replace temp file");
+
+    // Ensure that the original content is still served consistently
+    let mut body = Vec::new();
+    reader.read_to_end(&mut body).expect("failed to read replaced file");
+    assert_eq!(body, b"Testolope");
+    fs::remove_file(&path).expect("failed to delete temp file");
+}
@@ -0,0 +1,59 @@
+#![cfg(all(target_os = "linux", feature = "seekhole"))]
+
+mod common;
+
+use ehttpd::http::Response;
+use ehttpd_range::holes::HoleReader;
+use ehttpd_range::{FileRangeOptions, RangeResponse};
+use std::io::{ErrorKind, Read};
+
+#[test]
+fn hole_reader() {
+    let file = common::sparse_tempfile("ehttpd-range.test-hole-reader.tmp", 16 << 20, 8 << 20, b"Testolope");
+
+    // Read a range that spans a hole, the data and the trailing hole
+    let mut reader = HoleReader::new(file, ((8 << 20) - 3)..((8 << 20) + 12));
+    let mut read = Vec::new();
+    reader.read_to_end(&mut read).expect("failed to read range");
+    assert_eq!(read, b"\0\0\0Testolope\0\0\0");
+}
+
+#[test]
+fn hole_reader_full() {
+    let file = common::sparse_tempfile("ehttpd-range.test-hole-reader-full.tmp", 16 << 20, 8 << 20, b"Testolope");
+
+    // Read the whole file and compare it against the expected content
+    let mut reader = HoleReader::new(file, 0..(16 << 20));
+    let mut read = Vec::new();
+    reader.read_to_end(&mut read).expect("failed to read file");
+    assert_eq!(read.len(), 16 << 20);
+    assert_eq!(&read[(8 << 20)..(8 << 20) + 9], b"Testolope");
+    assert_eq!(read.iter().filter(|byte| **byte != 0).count(), 9);
+}
+
+#[test]
+fn hole_reader_truncated() {
+    let file = common::sparse_tempfile("ehttpd-range.test-hole-reader-truncated.tmp", 16 << 20, 8 << 20, b"Testolope");
+    let truncate = file.try_clone().expect("failed to clone file handle");
+
+    // Truncate the file below the range and ensure that the missing tail is not served as a hole
+    let mut reader = HoleReader::new(file, 0..(16 << 20));
+    truncate.set_len(1 << 20).expect("failed to truncate temp file");
+    let error = reader.read_to_end(&mut Vec::new()).expect_err("unexpected success on truncated file");
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn file_range_skip_holes() {
+    let file = common::sparse_tempfile("ehttpd-range.test-file-range-skip-holes.tmp", 4 << 20, 1 << 20, b"Testolope");
+
+    // Serve a range with hole skipping
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    let options = FileRangeOptions { skip_holes: true, ..Default::default() };
+    response.set_body_file_range_with(file, ((1 << 20) - 2)..((1 << 20) + 4), &options).expect("failed to set range");
+
+    // Validate the body
+    let mut body = Vec::new();
+    response.body.read_to_end(&mut body).expect("failed to read body");
+    assert_eq!(body, b"\0\0Test");
+}
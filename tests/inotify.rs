@@ -0,0 +1,49 @@
+#![cfg(all(target_os = "linux", feature = "inotify"))]
+
+use ehttpd_range::filecache::FileCache;
+use ehttpd_range::inotify::CacheWatcher;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::{env, fs, process, thread};
+
+/// Waits until the condition is true or a timeout occurs
+fn wait_until<F>(condition: F) -> bool
+where
+    F: Fn() -> bool,
+{
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+#[test]
+fn watcher_invalidates() {
+    // Create the test directory and cache a file
+    let dir = env::temp_dir().join(format!("ehttpd-range.test-inotify.{}", process::id()));
+    fs::create_dir_all(&dir).expect("failed to create temp dir");
+    fs::write(dir.join("a"), b"Testolope").expect("failed to create temp file");
+    fs::write(dir.join("b"), b"Testolope").expect("failed to create temp file");
+    let cache = Arc::new(FileCache::new(4, Duration::from_secs(3600)));
+    cache.get(dir.join("a")).expect("failed to open file");
+    cache.get(dir.join("b")).expect("failed to open file");
+
+    // Watch the directory and modify one file
+    let watcher = CacheWatcher::new(cache.clone()).expect("failed to create watcher");
+    watcher.watch(&dir).expect("failed to watch directory");
+    fs::write(dir.join("a"), b"Test").expect("failed to modify temp file");
+
+    // Ensure that only the modified entry has been evicted
+    assert!(wait_until(|| !cache.contains(dir.join("a"))));
+    assert!(cache.contains(dir.join("b")));
+
+    // Ensure that removals are picked up too
+    fs::remove_file(dir.join("b")).expect("failed to delete temp file");
+    assert!(wait_until(|| !cache.contains(dir.join("b"))));
+    drop(watcher);
+    fs::remove_dir_all(&dir).expect("failed to delete temp dir");
+}
@@ -0,0 +1,60 @@
+#![cfg(all(unix, feature = "mmap"))]
+
+mod common;
+
+use ehttpd::http::Response;
+use ehttpd_range::mmap::MappedFile;
+use ehttpd_range::{RangeResponse, Representation};
+use std::fs::{self, File};
+use std::io::Read;
+
+#[test]
+fn mapped_file() {
+    // Map the test file and serve a range
+    let file = common::tempfile("ehttpd-range.test-mmap.tmp", b"Testolope");
+    // SAFETY: The temp file is private to this test and not modified while it is mapped
+    let file = unsafe { MappedFile::new(file) }.expect("failed to map file");
+    assert!(file.is_mapped());
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_body_range(&file, 2..=5).expect("failed to set range body");
+
+    // Validate the body
+    let mut body = Vec::new();
+    response.body.read_to_end(&mut body).expect("failed to read body");
+    assert_eq!(body, b"stol");
+
+    // Test out-of-bounds range
+    assert!(file.open_range(4..10).is_err());
+}
+
+#[test]
+fn mapped_file_fallback() {
+    // Ensure that an empty test file which cannot be mapped falls back to regular reads
+    let file = common::tempfile("ehttpd-range.test-mmap-empty.tmp", b"");
+    // SAFETY: The temp file is private to this test and not modified while it is mapped
+    let file = unsafe { MappedFile::new(file) }.expect("failed to open file");
+    assert!(!file.is_mapped());
+    let mut body = Vec::new();
+    file.open_range(0..0).expect("failed to open range").read_to_end(&mut body).expect("failed to read body");
+    assert!(body.is_empty());
+}
+
+#[test]
+fn mapped_file_fallback_interleaved() {
+    // Create an empty test file which cannot be mapped
+    let path = common::temppath("ehttpd-range.test-mmap-interleaved.tmp", b"");
+    let file = File::open(&path).expect("failed to open temp file");
+    // SAFETY: The file is empty and thus never mapped
+    let file = unsafe { MappedFile::new(file) }.expect("failed to open file");
+    assert!(!file.is_mapped());
+
+    // Grow the file and open both ranges before reading either of them
+    fs::write(&path, b"Testolope").expect("failed to write temp file");
+    let mut first = file.open_range(0..4).expect("failed to open range");
+    let mut second = file.open_range(5..9).expect("failed to open range");
+    let (mut first_buf, mut second_buf) = (Vec::new(), Vec::new());
+    first.read_to_end(&mut first_buf).expect("failed to read range");
+    second.read_to_end(&mut second_buf).expect("failed to read range");
+    assert_eq!((first_buf.as_slice(), second_buf.as_slice()), (b"Test".as_slice(), b"lope".as_slice()));
+    fs::remove_file(&path).expect("failed to delete temp file");
+}
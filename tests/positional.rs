@@ -0,0 +1,49 @@
+mod common;
+
+use ehttpd::http::Response;
+use ehttpd_range::RangeResponse;
+use ehttpd_range::positional::PositionalReader;
+use std::io::Read;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn positional_reader() {
+    let file = Arc::new(common::tempfile("ehttpd-range.test-positional-reader.tmp", b"Testolope"));
+
+    // Read interleaved from two readers over the same file
+    let (mut first, mut second) = (PositionalReader::new(file.clone(), 0..4), PositionalReader::new(file, 4..9));
+    let (mut first_buf, mut second_buf) = ([0; 2], [0; 3]);
+    first.read_exact(&mut first_buf).expect("failed to read from file");
+    second.read_exact(&mut second_buf).expect("failed to read from file");
+    assert_eq!((&first_buf, &second_buf), (b"Te", b"olo"));
+    assert_eq!((first.remaining(), second.remaining()), (2, 2));
+}
+
+#[test]
+fn shared_file_concurrent() {
+    let data: Vec<u8> = (0..=255).cycle().take(1024 * 1024).collect();
+    let file = Arc::new(common::tempfile("ehttpd-range.test-positional-shared.tmp", &data));
+
+    // Serve many concurrent ranges over the same file descriptor
+    let threads: Vec<_> = (0..8u64)
+        .map(|index| {
+            let file = file.clone();
+            thread::spawn(move || {
+                let range = (index * 100_000)..(index * 100_000 + 65_537);
+                let mut response: Response = RangeResponse::new_206_partial_content();
+                response.set_body_range(&file, range.clone()).expect("failed to set range body");
+
+                let mut body = Vec::new();
+                response.body.read_to_end(&mut body).expect("failed to read body");
+                (range, body)
+            })
+        })
+        .collect();
+
+    // Validate the bodies
+    for thread in threads {
+        let (range, body) = thread.join().expect("thread has panicked");
+        assert_eq!(body, data[range.start as usize..range.end as usize]);
+    }
+}
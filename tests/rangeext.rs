@@ -38,3 +38,59 @@ fn to_exclusive() {
     let range = Range::<u64>::from_range_bounds(7.., 0, u64::MAX).expect("failed to convert range");
     assert_eq!(range, 7..u64::MAX);
 }
+
+#[test]
+fn chunks() {
+    // Test even and uneven chunks
+    let chunks: Vec<_> = (0u64..8).chunks(4).collect();
+    assert_eq!(chunks, [0..4, 4..8]);
+    let chunks: Vec<_> = (1u64..=9).chunks(4).collect();
+    assert_eq!(chunks, [1..=4, 5..=8, 9..=9]);
+
+    // Test empty range
+    let chunks: Vec<_> = (7usize..7).chunks(4).collect();
+    assert!(chunks.is_empty());
+
+    // Test chunks at the end of the integer space
+    let chunks: Vec<_> = ((u64::MAX - 4)..=u64::MAX).chunks(2).collect();
+    assert_eq!(chunks, [(u64::MAX - 4)..=(u64::MAX - 3), (u64::MAX - 2)..=(u64::MAX - 1), u64::MAX..=u64::MAX]);
+    let chunks: Vec<_> = (0..=u64::MAX).chunks(u64::MAX).collect();
+    assert_eq!(chunks, [0..=(u64::MAX - 1), u64::MAX..=u64::MAX]);
+}
+
+#[test]
+fn split_parts() {
+    // Test even and uneven parts
+    let parts: Vec<_> = (0u64..9).split_parts(3).collect();
+    assert_eq!(parts, [0..3, 3..6, 6..9]);
+    let parts: Vec<_> = (0u64..=10).split_parts(3).collect();
+    assert_eq!(parts, [0..=3, 4..=7, 8..=10]);
+
+    // Test more parts than elements
+    let parts: Vec<_> = (0usize..2).split_parts(5).collect();
+    assert_eq!(parts, [0..1, 1..2]);
+
+    // Test the entire integer space
+    let parts: Vec<_> = (0..=u64::MAX).split_parts(1).collect();
+    assert_eq!(parts, [0..=u64::MAX]);
+    let parts: Vec<_> = (0..=u64::MAX).split_parts(2).collect();
+    assert_eq!(parts, [0..=(u64::MAX / 2), (u64::MAX / 2 + 1)..=u64::MAX]);
+    let parts: Vec<_> = (0..=u64::MAX).split_parts(3).collect();
+    assert_eq!(parts.len(), 3);
+    assert_eq!(parts.last().map(|part| *part.end()), Some(u64::MAX));
+}
+
+#[test]
+fn aligned_chunks() {
+    // Test partial first and last chunks
+    let chunks: Vec<_> = (1000u64..9000).aligned_chunks(4096).collect();
+    assert_eq!(chunks, [1000..4096, 4096..8192, 8192..9000]);
+
+    // Test an already aligned range
+    let chunks: Vec<_> = (0u64..=8191).aligned_chunks(4096).collect();
+    assert_eq!(chunks, [0..=4095, 4096..=8191]);
+
+    // Test chunks at the end of the integer space
+    let chunks: Vec<_> = ((u64::MAX - 5)..=u64::MAX).aligned_chunks(4).collect();
+    assert_eq!(chunks, [(u64::MAX - 5)..=(u64::MAX - 4), (u64::MAX - 3)..=u64::MAX]);
+}
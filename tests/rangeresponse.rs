@@ -0,0 +1,233 @@
+mod common;
+
+use ehttpd::bytes::{Data, Source};
+use ehttpd::error::Error;
+use ehttpd::http::Response;
+use ehttpd_range::advice::{Access, AdvicePolicy};
+use ehttpd_range::cursor::DataCursor;
+use ehttpd_range::{FileRangeOptions, RangeResponse, Representation};
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A representation with fixed metadata
+struct FakeRepresentation;
+impl Representation for FakeRepresentation {
+    fn complete_length(&self) -> Result<Option<u64>, Error> {
+        Ok(Some(9))
+    }
+
+    fn etag(&self) -> Result<Option<Data>, Error> {
+        Ok(Some(Data::from(r#""v1""#)))
+    }
+    fn last_modified(&self) -> Result<Option<SystemTime>, Error> {
+        Ok(Some(UNIX_EPOCH + Duration::from_secs(784111777)))
+    }
+    fn content_type(&self) -> Option<Data> {
+        Some(Data::from("text/plain"))
+    }
+
+    fn open_range(&self, range: Range<u64>) -> Result<Source, Error> {
+        let data = &b"Testolope"[range.start as usize..range.end as usize];
+        Ok(Source::from(data))
+    }
+}
+
+/// Serializes the response and returns the header fields and the body
+fn serialize(mut response: Response) -> (String, Vec<u8>) {
+    let mut serialized = Vec::new();
+    response.to_stream(&mut serialized).expect("failed to serialize response");
+
+    // Split header and body
+    let header_len = serialized.windows(4).position(|window| window == b"\r\n\r\n").expect("missing header end");
+    let body = serialized.split_off(header_len + 4);
+    let header = String::from_utf8(serialized).expect("header is not valid UTF-8");
+    (header, body)
+}
+
+#[test]
+fn reader_range() {
+    // Serve a range from a cursor
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    let reader = Cursor::new(b"Testolope".to_vec());
+    response.set_body_reader_range(reader, 2..=5).expect("failed to set reader range");
+
+    // Validate the response
+    let (header, body) = serialize(response);
+    assert!(header.contains("Content-Range: bytes 2-5/9\r\n"));
+    assert!(header.contains("Content-Length: 4\r\n"));
+    assert_eq!(body, b"stol");
+}
+
+#[test]
+fn reader_range_len() {
+    // Serve a range from a cursor with an explicit length
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    let reader = Cursor::new(b"Testolope".to_vec());
+    response.set_body_reader_range_len(reader, 9, 4..).expect("failed to set reader range");
+
+    // Validate the response
+    let (header, body) = serialize(response);
+    assert!(header.contains("Content-Range: bytes 4-8/9\r\n"));
+    assert!(header.contains("Content-Length: 5\r\n"));
+    assert_eq!(body, b"olope");
+}
+
+#[test]
+fn reader_range_invalid() {
+    // Test range exceeding the reader
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    let reader = Cursor::new(b"Testolope".to_vec());
+    assert!(response.set_body_reader_range(reader, 4..10).is_err());
+
+    // Test non-206 response
+    let mut response = Response::new_200_ok();
+    let reader = Cursor::new(b"Testolope".to_vec());
+    assert!(response.set_body_reader_range(reader, 4..).is_err());
+}
+
+#[test]
+fn representation_range() {
+    // Serve a range from a representation
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_body_range(&FakeRepresentation, ..=3).expect("failed to set representation range");
+
+    // Validate the response
+    let (header, body) = serialize(response);
+    assert!(header.contains("Content-Range: bytes 0-3/9\r\n"));
+    assert!(header.contains("Content-Length: 4\r\n"));
+    assert!(header.contains("ETag: \"v1\"\r\n"));
+    assert!(header.contains("Last-Modified: Sun, 06 Nov 1994 08:49:37 GMT\r\n"));
+    assert!(header.contains("Content-Type: text/plain\r\n"));
+    assert_eq!(body, b"Test");
+}
+
+#[test]
+fn representation_builtin() {
+    // Test the in-memory representations
+    let arc: Arc<[u8]> = Arc::from(b"Testolope".as_slice());
+    let representations: [&dyn Representation; 3] = [&Data::from(b"Testolope"), &b"Testolope".to_vec(), &arc];
+    for representation in representations {
+        let mut response: Response = RangeResponse::new_206_partial_content();
+        response.set_body_range(representation, 4..7).expect("failed to set representation range");
+
+        // Validate the response
+        let (header, body) = serialize(response);
+        assert!(header.contains("Content-Range: bytes 4-6/9\r\n"));
+        assert_eq!(body, b"olo");
+    }
+}
+
+#[test]
+fn representation_file_interleaved() {
+    let file = common::tempfile("ehttpd-range.test-representation-file-interleaved.tmp", b"Testolope");
+
+    // Open both ranges before reading either of them
+    let mut first = file.open_range(0..4).expect("failed to open range");
+    let mut second = file.open_range(5..9).expect("failed to open range");
+    let (mut first_buf, mut second_buf) = (Vec::new(), Vec::new());
+    first.read_to_end(&mut first_buf).expect("failed to read range");
+    second.read_to_end(&mut second_buf).expect("failed to read range");
+    assert_eq!((first_buf.as_slice(), second_buf.as_slice()), (b"Test".as_slice(), b"lope".as_slice()));
+}
+
+#[test]
+fn data_range_shared() {
+    // Serve a range from heap data
+    let backing: Arc<[u8]> = Arc::from(b"Testolope".as_slice());
+    let data = Data::Heap { data: backing.clone(), range: 0..backing.len() };
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_body_data_range(data, 2..=5).expect("failed to set data range");
+
+    // Ensure that the body references the same memory instead of a copy
+    assert_eq!(Arc::strong_count(&backing), 2);
+    let (header, body) = serialize(response);
+    assert!(header.contains("Content-Range: bytes 2-5/9\r\n"));
+    assert_eq!(body, b"stol");
+    assert_eq!(Arc::strong_count(&backing), 1);
+}
+
+#[test]
+fn data_range_static() {
+    static BACKING: &[u8] = b"Testolope";
+
+    // Ensure that views of static data point into the original slice
+    let mut cursor = DataCursor::new(Data::new_static(BACKING));
+    cursor.seek(SeekFrom::Start(2)).expect("failed to seek");
+    let Data::Static(view) = cursor.read_view(4).expect("failed to read view") else {
+        panic!("view of static data is not static");
+    };
+    assert_eq!(view.as_ptr(), BACKING[2..].as_ptr());
+
+    // Serve a range from static data and ensure that the body is a static view instead of a copy
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_body_data_range(Data::new_static(BACKING), 2..=5).expect("failed to set data range");
+    assert!(format!("{:?}", response.body).contains("Static([115, 116, 111, 108])"));
+    let (header, body) = serialize(response);
+    assert!(header.contains("Content-Range: bytes 2-5/9\r\n"));
+    assert_eq!(body, b"stol");
+}
+
+#[test]
+fn data_range() {
+    // Serve a range from data
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_body_data_range(b"Testolope".to_vec(), 4..=6).expect("failed to set data range");
+
+    // Validate the response
+    let (header, body) = serialize(response);
+    assert!(header.contains("Content-Range: bytes 4-6/9\r\n"));
+    assert!(header.contains("Content-Length: 3\r\n"));
+    assert_eq!(body, b"olo");
+}
+
+#[test]
+fn data_chunks_range() {
+    // Serve a range across multiple chunks including an empty one
+    let chunks = [Data::from(b"Tes"), Data::from(b""), Data::from(b"tol"), Data::from(b"ope")];
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_body_data_chunks_range(chunks.clone(), 2..8).expect("failed to set chunks range");
+
+    // Validate the response
+    let (header, body) = serialize(response);
+    assert!(header.contains("Content-Range: bytes 2-7/9\r\n"));
+    assert!(header.contains("Content-Length: 6\r\n"));
+    assert_eq!(body, b"stolop");
+
+    // Test an out-of-bounds range
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    assert!(response.set_body_data_chunks_range(chunks, 2..10).is_err());
+}
+
+#[test]
+fn file_range_advised() {
+    let file = common::tempfile("ehttpd-range.test-file-advised.tmp", b"Testolope");
+
+    // Classify ranges
+    let advice = AdvicePolicy { sequential_threshold: 4, dontneed_threshold: Some(4) };
+    assert_eq!(advice.classify(3), Access::Random);
+    assert_eq!(advice.classify(4), Access::Sequential);
+    assert!(advice.should_drop(&(0..4)));
+
+    // Serve a range with page-cache hints
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    let options = FileRangeOptions { advice: Some(advice), ..Default::default() };
+    response.set_body_file_range_with(file, 1..=7, &options).expect("failed to set file range");
+
+    // Validate the response
+    let (header, body) = serialize(response);
+    assert!(header.contains("Content-Range: bytes 1-7/9\r\n"));
+    assert_eq!(body, b"estolop");
+}
+
+#[test]
+#[cfg(unix)]
+fn file_range_special() {
+    // Ensure that character devices are rejected
+    let file = File::open("/dev/null").expect("failed to open /dev/null");
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    let error = response.set_body_file_range(file, 0..0).expect_err("unexpected range of character device");
+    assert!(error.error.contains("character device"));
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use ehttpd::http::Response;
+use ehttpd_range::RangeResponse;
+use ehttpd_range::readplan::{PlannedRead, ReadPlanner};
+use std::fs::File;
+use std::io::Read;
+use std::str;
+use std::sync::Arc;
+
+/// Creates a temp file with the bytes `0..=255`
+fn tempfile(name: &str) -> (File, Vec<u8>) {
+    let data: Vec<u8> = (0..=255).collect();
+    (common::tempfile(name, &data), data)
+}
+
+#[test]
+fn plan_merges_nearby_ranges() {
+    // Plan unordered ranges with small gaps, a duplicate and a far-away range
+    let planner = ReadPlanner { max_gap: 4, max_read: 64 };
+    let plan = planner.plan(&[20..24, 0..4, 6..10, 100..110, 0..2]);
+    assert_eq!(
+        plan.reads(),
+        [
+            PlannedRead { range: 0..10, parts: vec![4, 1, 2] },
+            PlannedRead { range: 20..24, parts: vec![0] },
+            PlannedRead { range: 100..110, parts: vec![3] }
+        ]
+    );
+
+    // Validate the counters; the first read is repeated since the client order returns to it
+    let stats = plan.stats();
+    assert_eq!((stats.requested, stats.reads), (5, 3));
+    assert_eq!((stats.naive_reads, stats.planned_reads, stats.saved_reads()), (5, 4, 1));
+}
+
+#[test]
+fn plan_respects_max_read() {
+    let planner = ReadPlanner { max_gap: 4, max_read: 8 };
+    let plan = planner.plan(&[0..4, 6..10]);
+    assert_eq!(plan.reads().len(), 2);
+
+    // Ensure that large ranges are split
+    let plan = planner.plan(&[0..20, 20..20]);
+    let reads: Vec<_> = plan.reads().iter().map(|read| read.range.clone()).collect();
+    assert_eq!(reads, [0..8, 8..16, 16..20]);
+}
+
+#[test]
+fn plan_reader() {
+    let (file, data) = tempfile("ehttpd-range.test-plan-reader.tmp");
+    let ranges = [200..210, 10..20, 15..25, 30..31, 3..30];
+    let plan = ReadPlanner { max_gap: 8, max_read: 16 }.plan(&ranges);
+    let stats = plan.stats();
+
+    // Ensure that the parts are streamed in the requested order
+    let (mut reader, mut read) = (plan.reader(Arc::new(file)), Vec::new());
+    reader.read_to_end(&mut read).expect("failed to read parts");
+    let expected: Vec<u8> =
+        ranges.iter().flat_map(|range| data[range.start as usize..range.end as usize].to_vec()).collect();
+    assert_eq!(read, expected);
+
+    // Ensure that the estimate matches the actual reads for a regular file
+    assert_eq!(reader.read_calls(), stats.planned_reads);
+    assert!(stats.saved_reads() > 0);
+}
+
+#[test]
+fn file_ranges_multipart() {
+    let (file, _) = tempfile("ehttpd-range.test-file-ranges-multipart.tmp");
+
+    // Serve multiple ranges
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_content_type("application/octet-stream");
+    response.set_body_file_ranges(file, &[65..=67, 97..=99], &ReadPlanner::default()).expect("failed to set ranges");
+
+    // Get the boundary
+    let (_, content_type) = (response.fields.iter())
+        .find(|(key, _)| key.eq_ignore_ascii_case(b"Content-Type"))
+        .expect("missing content type");
+    let content_type = str::from_utf8(content_type).expect("content type is not valid UTF-8");
+    let boundary = content_type.strip_prefix("multipart/byteranges; boundary=").expect("invalid content type");
+
+    // Validate the body and the content length
+    let expected = format!(
+        "--{boundary}\r\nContent-Type: application/octet-stream\r\nContent-Range: bytes 65-67/256\r\n\r\nABC\r\n\
+         --{boundary}\r\nContent-Type: application/octet-stream\r\nContent-Range: bytes 97-99/256\r\n\r\nabc\r\n\
+         --{boundary}--\r\n"
+    );
+    let content_length = response.content_length().expect("invalid content length");
+    assert_eq!(content_length, Some(expected.len() as u64));
+    let mut body = String::new();
+    response.body.read_to_string(&mut body).expect("failed to read body");
+    assert_eq!(body, expected);
+
+    // Ensure that a single range is not served as multipart body
+    let (file, _) = tempfile("ehttpd-range.test-file-ranges-single.tmp");
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_content_type("application/octet-stream");
+    response.set_body_file_ranges(file, &[65..=67], &ReadPlanner::default()).expect("failed to set ranges");
+    assert!(response.fields.iter().any(|(key, value)| key == "Content-Type" && value == "application/octet-stream"));
+    assert!(response.fields.iter().any(|(key, value)| key == "Content-Range" && value == "bytes 65-67/256"));
+    let mut body = String::new();
+    response.body.read_to_string(&mut body).expect("failed to read body");
+    assert_eq!(body, "ABC");
+
+    // Test invalid and empty ranges
+    let (file, _) = tempfile("ehttpd-range.test-file-ranges-invalid.tmp");
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    assert!(response.set_body_file_ranges(file, &[0..4, 250..257], &ReadPlanner::default()).is_err());
+}
@@ -0,0 +1,62 @@
+#![cfg(all(target_os = "linux", feature = "sendfile"))]
+
+mod common;
+
+use ehttpd::http::Response;
+use ehttpd_range::RangeResponse;
+use ehttpd_range::sendfile::{self, SendfileBody};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::net::{TcpListener, TcpStream};
+use std::{env, thread};
+
+#[test]
+fn sendfile_socket() {
+    // Connect a socket pair
+    let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind listener");
+    let address = listener.local_addr().expect("failed to get listener address");
+    let reader = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("failed to accept connection");
+        let mut received = Vec::new();
+        stream.read_to_end(&mut received).expect("failed to read from socket");
+        received
+    });
+
+    // Send the response
+    let file = common::tempfile("ehttpd-range.test-sendfile-socket.tmp", b"Testolope");
+    let body = SendfileBody::new(file, 2..=5).expect("failed to create body");
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    let mut stream = TcpStream::connect(address).expect("failed to connect");
+    sendfile::to_stream(&mut response, body, &mut stream).expect("failed to send response");
+    drop(stream);
+
+    // Validate the response
+    let received = reader.join().expect("reader thread has panicked");
+    let received = String::from_utf8(received).expect("response is not valid UTF-8");
+    assert!(received.starts_with("HTTP/1.1 206 Partial Content\r\n"));
+    assert!(received.contains("Content-Range: bytes 2-5/9\r\n"));
+    assert!(received.ends_with("Content-Length: 4\r\n\r\nstol"));
+}
+
+#[test]
+fn sendfile_fallback() {
+    // Create a regular file as sink
+    let path = env::temp_dir().join("ehttpd-range.test-sendfile-sink.tmp");
+    let mut sink = (File::options().read(true).write(true).create(true).truncate(true))
+        .open(&path)
+        .expect("failed to create temp file");
+    fs::remove_file(&path).expect("failed to delete temp file");
+
+    // Transmit into the regular file
+    let file = common::tempfile("ehttpd-range.test-sendfile-source.tmp", b"Testolope");
+    let mut body = SendfileBody::new(file, 4..).expect("failed to create body");
+    let transmitted = body.transmit(&mut sink).expect("failed to transmit body");
+    assert_eq!(transmitted, 5);
+    assert_eq!(body.remaining(), 0);
+
+    // Validate the sink
+    let mut received = Vec::new();
+    sink.seek(SeekFrom::Start(0)).expect("failed to seek sink");
+    sink.read_to_end(&mut received).expect("failed to read sink");
+    assert_eq!(received, b"olope");
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use ehttpd::http::Response;
+use ehttpd_range::sparse::{Extent, Sparse};
+use ehttpd_range::{RangeResponse, Representation};
+use std::io::Read;
+use std::sync::Arc;
+
+#[test]
+fn sparse_extents() {
+    let file = Arc::new(common::tempfile("ehttpd-range.test-sparse-extents.tmp", b"TestolopeAB"));
+    let extents = [
+        Extent { offset: 4, file: file.clone(), file_offset: 0, len: 4 },
+        Extent { offset: 8, file: file.clone(), file_offset: 9, len: 2 },
+    ];
+    let sparse = Sparse::new(14, extents).expect("failed to create sparse representation");
+    assert_eq!(sparse.complete_length().expect("failed to get length"), Some(14));
+
+    // Read the whole image including leading, inner and trailing holes
+    let mut read = Vec::new();
+    sparse.open_range(0..14).expect("failed to open range").read_to_end(&mut read).expect("failed to read range");
+    assert_eq!(read, b"\0\0\0\0TestAB\0\0\0\0");
+
+    // Reject overlapping and oversized extents
+    let overlapping = [
+        Extent { offset: 0, file: file.clone(), file_offset: 0, len: 4 },
+        Extent { offset: 2, file: file.clone(), file_offset: 0, len: 4 },
+    ];
+    assert!(Sparse::new(14, overlapping).is_err());
+    assert!(Sparse::new(4, [Extent { offset: 2, file: file.clone(), file_offset: 0, len: 4 }]).is_err());
+
+    // Reject extents that exceed the backing file
+    assert!(Sparse::new(100, [Extent { offset: 0, file, file_offset: 8, len: 4 }]).is_err());
+}
+
+#[test]
+fn sparse_response() {
+    let file = Arc::new(common::tempfile("ehttpd-range.test-sparse-response.tmp", b"Testolope"));
+    let sparse = Sparse::new(1 << 40, [Extent { offset: 1 << 39, file, file_offset: 0, len: 9 }])
+        .expect("failed to create sparse representation");
+
+    // Serve a range around the extent of a huge sparse image
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    let start = (1 << 39) - 2;
+    response.set_body_range(&sparse, start..=start + 5).expect("failed to set body range");
+    let content_range = format!("bytes {start}-{}/{}", start + 5, 1u64 << 40);
+    assert!(response.fields.iter().any(|(key, value)| key == "Content-Range" && *value == *content_range));
+
+    // Validate the body
+    let mut body = Vec::new();
+    response.body.read_to_end(&mut body).expect("failed to read body");
+    assert_eq!(body, b"\0\0Test");
+}
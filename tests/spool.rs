@@ -0,0 +1,72 @@
+use ehttpd::http::Response;
+use ehttpd_range::spool::{Spool, SpoolOptions, SpoolStore};
+use ehttpd_range::{RangeResponse, Representation};
+use std::io::Read;
+use std::time::Duration;
+
+#[test]
+fn spool_memory_and_file() {
+    let data: Vec<u8> = (0..=255).cycle().take(64 * 1024).collect();
+    let options = SpoolOptions { memory_threshold: 1024, ..Default::default() };
+
+    // Spool small content into memory and large content into a file
+    let small = Spool::new(&data[..1024], &options).expect("failed to spool content");
+    let large = Spool::new(data.as_slice(), &options).expect("failed to spool content");
+    assert!(small.is_in_memory());
+    assert!(!large.is_in_memory());
+    assert_eq!(large.complete_length().expect("failed to get length"), Some(data.len() as u64));
+
+    // Read a range from both spools
+    for (spool, len) in [(&small, 1024), (&large, data.len())] {
+        let mut read = Vec::new();
+        let mut source = spool.open_range(100..(len as u64 - 100)).expect("failed to open range");
+        source.read_to_end(&mut read).expect("failed to read range");
+        assert_eq!(read, &data[100..len - 100]);
+    }
+
+    // The entity tag is derived from the content
+    let again = Spool::new(data.as_slice(), &SpoolOptions::default()).expect("failed to spool content");
+    assert_eq!(large.etag().expect("failed to get etag"), again.etag().expect("failed to get etag"));
+    assert_ne!(small.etag().expect("failed to get etag"), large.etag().expect("failed to get etag"));
+}
+
+#[test]
+fn spool_response() {
+    let options = SpoolOptions { memory_threshold: 4, ..Default::default() };
+    let spool = Spool::new(b"Testolope".as_slice(), &options).expect("failed to spool content");
+
+    // Serve a range from the spool
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_body_range(&spool, 4..=6).expect("failed to set body range");
+    assert!(response.fields.iter().any(|(key, value)| key == "Content-Range" && value == "bytes 4-6/9"));
+    assert!(response.fields.iter().any(|(key, _)| key == "ETag"));
+
+    // Validate the body
+    let mut body = Vec::new();
+    response.body.read_to_end(&mut body).expect("failed to read body");
+    assert_eq!(body, b"olo");
+}
+
+#[test]
+fn spool_store() {
+    let store = SpoolStore::new(SpoolOptions::default());
+
+    // Generate once and reuse the spool
+    let first = store.get_or_spool("key", || Ok(b"Testolope".as_slice())).expect("failed to spool content");
+    let second = store.get_or_spool("key", || -> Result<&[u8], _> { panic!("content was generated twice") });
+    let second = second.expect("failed to get spool");
+    assert_eq!(first.etag().expect("failed to get etag"), second.etag().expect("failed to get etag"));
+
+    // Unrepresentable expiry times never expire
+    let store = SpoolStore::new(SpoolOptions { expiry: Duration::MAX, ..Default::default() });
+    let spool = store.get_or_spool("key", || Ok(b"Testolope".as_slice())).expect("failed to spool content");
+    assert!(!spool.is_expired());
+
+    // Expired spools are purged and regenerated
+    let store = SpoolStore::new(SpoolOptions { expiry: Duration::ZERO, ..Default::default() });
+    let spool = store.get_or_spool("key", || Ok(b"Testolope".as_slice())).expect("failed to spool content");
+    assert!(spool.is_expired());
+    assert!(store.get("key").is_none());
+    store.purge_expired();
+    assert!(store.remove("key").is_none());
+}
@@ -0,0 +1,59 @@
+use ehttpd::http::Response;
+use ehttpd_range::synthetic::Synthetic;
+use ehttpd_range::{RangeResponse, Representation};
+use std::io::Read;
+
+/// Reads the given range from the representation
+fn read_range(representation: &Synthetic, start: u64, end: u64) -> Vec<u8> {
+    let mut read = Vec::new();
+    let mut source = representation.open_range(start..end).expect("failed to open range");
+    source.read_to_end(&mut read).expect("failed to read range");
+    read
+}
+
+#[test]
+fn synthetic_zeros_and_pattern() {
+    assert_eq!(read_range(&Synthetic::zeros(16), 3, 7), [0; 4]);
+
+    // Read patterns across their boundaries
+    let pattern = Synthetic::pattern(b"Testolope".to_vec(), 100).expect("failed to create pattern");
+    assert_eq!(read_range(&pattern, 7, 25), b"peTestolopeTestolo");
+    assert_eq!(read_range(&pattern, 98, 100), b"eT");
+    assert!(Synthetic::pattern(Vec::new(), 100).is_err());
+}
+
+#[test]
+fn synthetic_random() {
+    let random = Synthetic::random(7, 4096);
+    let full = read_range(&random, 0, 4096);
+
+    // Any range must match the corresponding slice of the full stream
+    for (start, end) in [(0, 1), (3, 11), (1000, 1003), (4095, 4096)] {
+        assert_eq!(read_range(&random, start, end), &full[start as usize..end as usize]);
+    }
+
+    // Different seeds yield different streams, but the same seed is deterministic
+    assert_ne!(read_range(&Synthetic::random(8, 4096), 0, 4096), full);
+    assert_eq!(read_range(&Synthetic::random(7, 4096), 0, 4096), full);
+    assert_eq!(
+        random.etag().expect("failed to get etag"),
+        Synthetic::random(7, 4096).etag().expect("failed to get etag")
+    );
+}
+
+#[test]
+fn synthetic_response() {
+    const LEN: u64 = 100 << 30;
+    let random = Synthetic::random(7, LEN);
+
+    // Serve the last bytes of a huge virtual body
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_body_range(&random, (LEN - 16)..).expect("failed to set body range");
+    let content_range = format!("bytes {}-{}/{LEN}", LEN - 16, LEN - 1);
+    assert!(response.fields.iter().any(|(key, value)| key == "Content-Range" && *value == *content_range));
+
+    // Validate the body
+    let mut body = Vec::new();
+    response.body.read_to_end(&mut body).expect("failed to read body");
+    assert_eq!(body, read_range(&random, LEN - 16, LEN));
+}
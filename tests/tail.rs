@@ -0,0 +1,100 @@
+mod common;
+
+use ehttpd::http::Response;
+use ehttpd_range::tail::{TailOptions, TailReader};
+use ehttpd_range::{FileRangeOptions, RangeResponse};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn unknown_length() {
+    // Set a bounded content range with an unknown complete length
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_content_range_unknown(1000..=1999).expect("failed to set content range");
+    assert!(response.fields.iter().any(|(key, value)| key == "Content-Range" && value == "bytes 1000-1999/*"));
+    assert!(response.set_content_range_unknown(1000..).is_err());
+
+    // Serve an open-ended range up to the current end of a growing file
+    let path = common::temppath("ehttpd-range.test-tail-unknown.tmp", b"Testolope");
+    let file = File::open(&path).expect("failed to open temp file");
+    let options = FileRangeOptions { unknown_length: true, ..Default::default() };
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_body_file_range_with(file, 4.., &options).expect("failed to set file range");
+    assert!(response.fields.iter().any(|(key, value)| key == "Content-Range" && value == "bytes 4-8/*"));
+    fs::remove_file(&path).expect("failed to delete temp file");
+}
+
+#[test]
+fn tail_follows_file() {
+    let path = common::temppath("ehttpd-range.test-tail-follow.tmp", b"Testolope");
+
+    // Create a tail body that stops after some bytes
+    let file = File::open(&path).expect("failed to open temp file");
+    let options = TailOptions { poll_interval: Duration::from_millis(5), max_len: Some(14), ..Default::default() };
+    let mut response = Response::new_200_ok();
+    response.set_body_file_tail(file, options).expect("failed to set tail body");
+    assert!(response.content_length().expect("invalid content length").is_none());
+    assert!(response.has_connection_close());
+
+    // Append to the file while the body is being read
+    let append_path = path.clone();
+    let appender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        let mut file = File::options().append(true).open(append_path).expect("failed to open temp file");
+        file.write_all(b" and more").expect("failed to append to temp file");
+    });
+    let mut body = Vec::new();
+    response.body.read_to_end(&mut body).expect("failed to read body");
+    appender.join().expect("appender thread has panicked");
+    assert_eq!(body, b"Testolope and ");
+    fs::remove_file(&path).expect("failed to delete temp file");
+}
+
+#[test]
+fn tail_idle_timeout() {
+    let path = common::temppath("ehttpd-range.test-tail-idle.tmp", b"Testolope");
+
+    // Ensure that the body ends once the file is idle
+    let file = File::open(&path).expect("failed to open temp file");
+    let options = TailOptions {
+        poll_interval: Duration::from_millis(5),
+        idle_timeout: Duration::from_millis(50),
+        ..Default::default()
+    };
+    let mut response = Response::new_200_ok();
+    response.set_body_file_tail(file, options).expect("failed to set tail body");
+    let mut body = Vec::new();
+    response.body.read_to_end(&mut body).expect("failed to read body");
+    assert_eq!(body, b"Testolope");
+    fs::remove_file(&path).expect("failed to delete temp file");
+}
+
+#[test]
+fn tail_slow_consumer() {
+    let path = common::temppath("ehttpd-range.test-tail-slow.tmp", b"Testolope");
+
+    // Ensure that empty reads return immediately
+    let file = File::open(&path).expect("failed to open temp file");
+    let options = TailOptions {
+        poll_interval: Duration::from_millis(5),
+        idle_timeout: Duration::from_millis(50),
+        ..Default::default()
+    };
+    let mut reader = TailReader::new(file, 0, options).expect("failed to create reader");
+    assert_eq!(reader.read(&mut []).expect("failed to read"), 0);
+
+    // Ensure that a consumer which is slower than the idle timeout still receives the available data
+    let mut body = Vec::new();
+    let mut buf = [0; 4];
+    loop {
+        thread::sleep(Duration::from_millis(60));
+        match reader.read(&mut buf).expect("failed to read") {
+            0 => break,
+            read => body.extend_from_slice(&buf[..read]),
+        }
+    }
+    assert_eq!(body, b"Testolope");
+    fs::remove_file(&path).expect("failed to delete temp file");
+}
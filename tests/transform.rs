@@ -0,0 +1,56 @@
+mod common;
+
+use ehttpd::http::Response;
+use ehttpd_range::RangeResponse;
+use ehttpd_range::transform::{Transform, TransformReader, Transformed};
+use std::io::Read;
+use std::sync::Arc;
+
+/// A toy counter-mode keystream that XORs each byte with a key derived from its offset
+#[derive(Debug, Clone, Copy)]
+struct XorStream(u8);
+impl Transform for XorStream {
+    fn apply(&mut self, offset: u64, buf: &mut [u8]) {
+        for (index, byte) in buf.iter_mut().enumerate() {
+            let position = offset + index as u64;
+            *byte ^= self.0.wrapping_add(position.wrapping_mul(31) as u8);
+        }
+    }
+}
+
+/// Encrypts the given plaintext with the toy keystream
+fn encrypt(plaintext: &[u8]) -> Vec<u8> {
+    let mut ciphertext = plaintext.to_vec();
+    XorStream(7).apply(0, &mut ciphertext);
+    ciphertext
+}
+
+#[test]
+fn transform_reader() {
+    let ciphertext = encrypt(b"Testolope");
+
+    // Decrypt a suffix starting at its absolute offset
+    let mut reader = TransformReader::new(&ciphertext[4..], XorStream(7), 4);
+    let mut read = Vec::new();
+    reader.read_to_end(&mut read).expect("failed to read stream");
+    assert_eq!(read, b"olope");
+    assert_eq!(reader.offset(), 9);
+}
+
+#[test]
+fn transformed_file_range() {
+    // Create the encrypted test file
+    let plaintext: Vec<u8> = (0..=255).cycle().take(64 * 1024).collect();
+    let file = common::tempfile("ehttpd-range.test-transformed-file.tmp", &encrypt(&plaintext));
+
+    // Serve a decrypted range
+    let representation = Transformed::new(Arc::new(file), XorStream(7));
+    let mut response: Response = RangeResponse::new_206_partial_content();
+    response.set_body_range(&representation, 1000..=40000).expect("failed to set body range");
+    assert!(response.fields.iter().any(|(key, value)| key == "Content-Range" && value == "bytes 1000-40000/65536"));
+
+    // Validate the body
+    let mut body = Vec::new();
+    response.body.read_to_end(&mut body).expect("failed to read body");
+    assert_eq!(body, &plaintext[1000..=40000]);
+}